@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+use super::Backend;
+
+/// A connected client as reported by `CLIENT LIST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub id: u64,
+    pub addr: String,
+    pub name: Option<String>,
+}
+
+// a listed connection and the signal asking its handler to close
+#[derive(Debug)]
+pub(crate) struct ClientEntry {
+    info: ClientInfo,
+    kill: Arc<Notify>,
+}
+
+/// Keeps a connection listed until it is dropped.
+#[derive(Debug)]
+pub struct ClientRegistration {
+    backend: Backend,
+    id: u64,
+    kill: Arc<Notify>,
+}
+
+impl ClientRegistration {
+    /// Resolves once `CLIENT KILL` selected this connection.
+    pub async fn killed(&self) {
+        self.kill.notified().await
+    }
+}
+
+impl Drop for ClientRegistration {
+    fn drop(&mut self) {
+        self.backend.clients.remove(&self.id);
+    }
+}
+
+impl Backend {
+    /// List the connection `id` until the returned guard is dropped.
+    pub fn register_client(&self, id: u64, addr: String) -> ClientRegistration {
+        let kill = Arc::new(Notify::new());
+        let info = ClientInfo {
+            id,
+            addr,
+            name: None,
+        };
+        self.clients.insert(
+            id,
+            ClientEntry {
+                info,
+                kill: kill.clone(),
+            },
+        );
+        ClientRegistration {
+            backend: self.clone(),
+            id,
+            kill,
+        }
+    }
+
+    pub fn set_client_name(&self, id: u64, name: Option<String>) {
+        if let Some(mut client) = self.clients.get_mut(&id) {
+            client.info.name = name;
+        }
+    }
+
+    /// Every registered connection, oldest first.
+    pub fn clients(&self) -> Vec<ClientInfo> {
+        let mut clients = self
+            .clients
+            .iter()
+            .map(|c| c.info.clone())
+            .collect::<Vec<_>>();
+        clients.sort_by_key(|c| c.id);
+        clients
+    }
+
+    /// Ask every connection matching `filter` to close, returning how many were signalled.
+    pub fn kill_clients(&self, filter: impl Fn(&ClientInfo) -> bool) -> usize {
+        self.clients
+            .iter()
+            .filter(|c| filter(&c.info))
+            // the permit is kept if the handler is not waiting yet
+            .inspect(|c| c.kill.notify_one())
+            .count()
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use super::{Backend, EvictionConfig};
+
+// same as the redis default
+const DEFAULT_DATABASES: usize = 16;
+
+/// Server wide settings, named after their redis.conf counterparts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendConfig {
+    /// maxmemory and maxmemory-policy, the cap is counted in keys rather than bytes
+    pub eviction: Option<EvictionConfig>,
+    /// stored for when SELECT is supported, the server binary refuses to start with another count
+    pub databases: usize,
+    /// stored for when AUTH is supported, the server binary refuses to start with one set
+    pub requirepass: Option<String>,
+    /// close connections idle for longer than this
+    pub timeout: Option<Duration>,
+    /// stored for when persistence is supported, the server binary warns that nothing is saved
+    pub appendonly: bool,
+    /// lock shards of the keyspace maps, rounded up to a power of two with a minimum of two
+    pub shards: Option<usize>,
+    /// keys the keyspace is pre-sized for
+    pub initial_capacity: usize,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            eviction: None,
+            databases: DEFAULT_DATABASES,
+            requirepass: None,
+            timeout: None,
+            appendonly: false,
+            shards: None,
+            initial_capacity: 0,
+        }
+    }
+}
+
+impl Backend {
+    pub fn config(&self) -> &BackendConfig {
+        &self.config
+    }
+}
@@ -0,0 +1,25 @@
+/// What happened to a key, published to [`Backend::subscribe_events`](super::Backend::subscribe_events).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOp {
+    Set,
+    HSet,
+    Del,
+    Expire,
+    // the key was removed because its deadline passed
+    Expired,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: String,
+    pub op: KeyOp,
+}
+
+impl KeyEvent {
+    pub fn new(key: impl Into<String>, op: KeyOp) -> Self {
+        Self {
+            key: key.into(),
+            op,
+        }
+    }
+}
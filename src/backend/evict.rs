@@ -0,0 +1,119 @@
+use std::{str::FromStr, time::Instant};
+
+use rand::seq::IteratorRandom;
+
+use super::Backend;
+
+/// How victims are picked once the backend holds more keys than allowed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// evict the key that was read or written the longest time ago
+    #[default]
+    AllKeysLru,
+    AllKeysRandom,
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "allkeys-lru" => Ok(EvictionPolicy::AllKeysLru),
+            "allkeys-random" => Ok(EvictionPolicy::AllKeysRandom),
+            _ => Err(format!("unsupported eviction policy: {}", s)),
+        }
+    }
+}
+
+/// Caps the number of keys, the closest thing to maxmemory for this backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvictionConfig {
+    pub max_keys: usize,
+    pub policy: EvictionPolicy,
+}
+
+impl Backend {
+    // the LRU clock, also reported by OBJECT IDLETIME
+    pub(super) fn record_access(&self, key: &str) {
+        self.access.insert(key.to_string(), Instant::now());
+    }
+
+    // called after every write, `written` is never chosen as a victim
+    pub(super) fn evict_if_needed(&self, written: &str) {
+        let Some(config) = self.config.eviction else {
+            return;
+        };
+        while self.map.len() + self.hmap.len() > config.max_keys {
+            let candidates = self.access.iter().filter(|e| e.key() != written);
+            let victim = match config.policy {
+                // an exact scan, only paid for by writes that overflow the cap
+                EvictionPolicy::AllKeysLru => candidates
+                    .min_by_key(|e| *e.value())
+                    .map(|e| e.key().clone()),
+                EvictionPolicy::AllKeysRandom => candidates
+                    .choose(&mut rand::thread_rng())
+                    .map(|e| e.key().clone()),
+            };
+            match victim {
+                Some(key) => {
+                    self.del(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EvictionConfig, EvictionPolicy};
+    use crate::{Backend, BulkString};
+
+    #[test]
+    fn test_policy_from_str() {
+        assert_eq!(
+            "allkeys-lru".parse::<EvictionPolicy>(),
+            Ok(EvictionPolicy::AllKeysLru)
+        );
+        assert_eq!(
+            "ALLKEYS-RANDOM".parse::<EvictionPolicy>(),
+            Ok(EvictionPolicy::AllKeysRandom)
+        );
+        assert!("volatile-lru".parse::<EvictionPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let backend = Backend::with_eviction(EvictionConfig {
+            max_keys: 3,
+            policy: EvictionPolicy::AllKeysLru,
+        });
+        for key in ["a", "b", "c"] {
+            backend.set(key.to_string(), BulkString::new(b"v").into());
+        }
+        // reading a makes b the least recently used key
+        assert!(backend.get("a").is_some());
+        backend.set("d".to_string(), BulkString::new(b"v").into());
+        backend.hset(
+            "e".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+
+        assert_eq!(backend.keys(), vec!["a", "d", "e"]);
+        assert_eq!(backend.access.len(), 3);
+    }
+
+    #[test]
+    fn test_random_eviction_keeps_the_cap() {
+        let backend = Backend::with_eviction(EvictionConfig {
+            max_keys: 5,
+            policy: EvictionPolicy::AllKeysRandom,
+        });
+        for i in 0..20 {
+            backend.set(format!("key{}", i), BulkString::new(b"v").into());
+        }
+        assert_eq!(backend.keys().len(), 5);
+        assert!(backend.exists("key19"));
+    }
+}
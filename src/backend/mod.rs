@@ -1,7 +1,47 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-use crate::RespFrame;
-use dashmap::DashMap;
+use crate::{BulkString, RespFrame, RespMap};
+use dashmap::{mapref::entry::Entry, DashMap};
+use rand::seq::SliceRandom;
+use tokio::{
+    sync::broadcast,
+    task::{AbortHandle, Id, JoinHandle},
+};
+
+mod clients;
+mod config;
+mod event;
+mod evict;
+mod slowlog;
+mod snapshot;
+mod stats;
+
+pub use clients::{ClientInfo, ClientRegistration};
+pub use config::BackendConfig;
+pub use event::{KeyEvent, KeyOp};
+pub use evict::{EvictionConfig, EvictionPolicy};
+pub use slowlog::SlowLogEntry;
+pub use snapshot::BackendSnapshot;
+pub use stats::CommandStats;
+
+// strings up to this length are stored inline with their header in redis
+const EMBSTR_SIZE_LIMIT: usize = 44;
+
+// collections with more elements than this are dropped on a background task by UNLINK
+const LAZYFREE_THRESHOLD: usize = 64;
+
+// max number of expired keys removed per pass of the active expiry cycle
+const EXPIRE_CYCLE_BATCH: usize = 20;
+
+// subscribers lagging further behind than this miss the oldest events
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
@@ -10,6 +50,34 @@ pub struct Backend(Arc<BackendInner>);
 pub struct BackendInner {
     pub(crate) map: DashMap<String, RespFrame>,
     pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    // key -> deadline, shared by every expiry command regardless of its unit
+    pub(crate) expires: DashMap<String, Instant>,
+    // the same moment on the monotonic and the wall clock, converts deadlines to unix time and back
+    clock: (Instant, SystemTime),
+    events: broadcast::Sender<KeyEvent>,
+    // every command executed by any connection, formatted for MONITOR clients
+    monitor: broadcast::Sender<String>,
+    config: BackendConfig,
+    // key -> last read or write
+    access: DashMap<String, Instant>,
+    // command name -> calls and execution time
+    stats: DashMap<&'static str, CommandStats>,
+    // write commands executed here, streamed to every connected replica
+    replication: broadcast::Sender<RespFrame>,
+    // commands share it, taken exclusively to snapshot or rebuild the keyspace in between them
+    barrier: RwLock<()>,
+    // while replicas listen writes run one at a time, so they are streamed in the order applied
+    write_order: Mutex<()>,
+    // the task applying the stream of our primary, when this instance is a replica
+    primary: Mutex<Option<AbortHandle>>,
+    // ids handed out to client connections
+    next_client_id: AtomicU64,
+    // id -> connections currently being served
+    clients: DashMap<u64, clients::ClientEntry>,
+    // cleared by DEBUG SET-ACTIVE-EXPIRE 0, keys then only expire when accessed
+    active_expire: AtomicBool,
+    // commands slower than the configured threshold
+    slowlog: Mutex<slowlog::SlowLog>,
 }
 
 impl Deref for Backend {
@@ -31,35 +99,897 @@ impl Default for BackendInner {
         Self {
             map: DashMap::new(),
             hmap: DashMap::new(),
+            expires: DashMap::new(),
+            clock: (Instant::now(), SystemTime::now()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            monitor: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            config: BackendConfig::default(),
+            access: DashMap::new(),
+            stats: DashMap::new(),
+            replication: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            barrier: RwLock::new(()),
+            write_order: Mutex::new(()),
+            primary: Mutex::new(None),
+            next_client_id: AtomicU64::new(1),
+            clients: DashMap::new(),
+            active_expire: AtomicBool::new(true),
+            slowlog: Mutex::new(Default::default()),
         }
     }
 }
 
 impl Backend {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(config: BackendConfig) -> Self {
+        let inner = match config.shards {
+            Some(shards) => {
+                let shards = shards.max(2).next_power_of_two();
+                BackendInner {
+                    map: DashMap::with_capacity_and_shard_amount(config.initial_capacity, shards),
+                    hmap: DashMap::with_shard_amount(shards),
+                    expires: DashMap::with_shard_amount(shards),
+                    access: DashMap::with_shard_amount(shards),
+                    ..Default::default()
+                }
+            }
+            None => BackendInner {
+                map: DashMap::with_capacity(config.initial_capacity),
+                ..Default::default()
+            },
+        };
+        Self(Arc::new(BackendInner { config, ..inner }))
+    }
+
+    /// A backend whose maps are split into `shards` locks and pre-sized for `initial_capacity` keys.
+    pub fn with_capacity(shards: usize, initial_capacity: usize) -> Self {
+        Self::new(BackendConfig {
+            shards: Some(shards),
+            initial_capacity,
+            ..Default::default()
+        })
+    }
+
+    /// A backend that evicts keys once it holds more than `config.max_keys`.
+    pub fn with_eviction(config: EvictionConfig) -> Self {
+        Self::new(BackendConfig {
+            eviction: Some(config),
+            ..Default::default()
+        })
     }
 
     pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+        self.expire_if_needed(key);
+        let value = self.map.get(key).map(|v| render(v.value().clone()));
+        if value.is_some() {
+            self.record_access(key);
+        }
+        value
     }
 
+    /// Store a string value, replacing the key's value of any type along with its expiry.
     pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+        self.set_with_deadline(key, value, None);
+    }
+
+    /// Like `set` but the key expires at `deadline`, `None` leaves it persistent.
+    ///
+    /// The deadline is written first, so the new value is never visible without it.
+    pub fn set_with_deadline(&self, key: String, value: RespFrame, deadline: Option<Instant>) {
+        self.write_deadline(&key, deadline);
+        self.store_string(key.clone(), value);
+        if deadline.is_some() {
+            self.notify(&key, KeyOp::Expire);
+        }
+    }
+
+    /// Replace the value at key with a whole hash expiring at `deadline`, `None` leaves it persistent.
+    ///
+    /// The hash is filled before it is inserted, so it is never visible partially written.
+    pub fn set_hash_with_deadline(
+        &self,
+        key: String,
+        fields: impl IntoIterator<Item = (String, RespFrame)>,
+        deadline: Option<Instant>,
+    ) {
+        self.write_deadline(&key, deadline);
+        self.hmap.insert(key.clone(), fields.into_iter().collect());
+        self.map.remove(&key);
+        self.record_access(&key);
+        self.evict_if_needed(&key);
+        self.notify(&key, KeyOp::HSet);
+        if deadline.is_some() {
+            self.notify(&key, KeyOp::Expire);
+        }
+    }
+
+    // goes before the value, so a new value never shows up without its deadline
+    fn write_deadline(&self, key: &str, deadline: Option<Instant>) {
+        match deadline {
+            Some(deadline) => {
+                self.expires.insert(key.to_string(), deadline);
+            }
+            None => {
+                self.expires.remove(key);
+            }
+        }
+    }
+
+    /// Like `set` but a pending expiry of the key survives the overwrite.
+    pub fn set_keepttl(&self, key: String, value: RespFrame) {
+        // an already expired key must not pass its deadline on to the new value
+        self.expire_if_needed(&key);
+        self.store_string(key, value);
+    }
+
+    fn store_string(&self, key: String, value: RespFrame) {
+        // SET overwrites a value of any type, the old one stays readable until the new one is in
+        self.map.insert(key.clone(), value);
+        self.hmap.remove(&key);
+        self.record_access(&key);
+        self.evict_if_needed(&key);
+        self.notify(&key, KeyOp::Set);
+    }
+
+    /// Read-modify-write a string value under the key lock, the key is left untouched when `f` fails.
+    pub fn update<E>(
+        &self,
+        key: String,
+        f: impl FnOnce(Option<&RespFrame>) -> Result<RespFrame, E>,
+    ) -> Result<RespFrame, E> {
+        self.expire_if_needed(&key);
+        let ret = match self.map.entry(key.clone()) {
+            Entry::Occupied(mut e) => match e.get() {
+                RespFrame::Integer(v) => f(Some(&render(RespFrame::Integer(*v)))),
+                v => f(Some(v)),
+            }
+            .inspect(|v| {
+                e.insert(v.clone());
+            }),
+            Entry::Vacant(e) => f(None).inspect(|v| {
+                e.insert(v.clone());
+            }),
+        };
+        if ret.is_ok() {
+            self.record_access(&key);
+            self.evict_if_needed(&key);
+            self.notify(&key, KeyOp::Set);
+        }
+        ret
+    }
+
+    /// Run `f` on the bytes of a string value under the key lock and store them back.
+    ///
+    /// The bytes are moved out of the map rather than copied when nothing else holds them.
+    /// A missing key is created empty when `create` is set, otherwise `f` is not called.
+    pub fn with_string_mut<T>(
+        &self,
+        key: String,
+        create: bool,
+        f: impl FnOnce(&mut Vec<u8>) -> T,
+    ) -> Option<T> {
+        self.expire_if_needed(&key);
+        let ret = match self.map.entry(key.clone()) {
+            Entry::Occupied(mut e) => {
+                let mut value = match e.get_mut() {
+                    RespFrame::BulkString(v) => Vec::from(std::mem::take(&mut v.0)),
+                    v => match render(v.clone()) {
+                        RespFrame::BulkString(v) => Vec::from(v.0),
+                        _ => return None,
+                    },
+                };
+                let ret = f(&mut value);
+                e.insert(BulkString::new(value).into());
+                ret
+            }
+            Entry::Vacant(e) if create => {
+                let mut value = vec![];
+                let ret = f(&mut value);
+                e.insert(BulkString::new(value).into());
+                ret
+            }
+            Entry::Vacant(_) => return None,
+        };
+        self.record_access(&key);
+        self.evict_if_needed(&key);
+        self.notify(&key, KeyOp::Set);
+        Some(ret)
+    }
+
+    /// Add `delta` to an integer string in place, returning the new value.
+    ///
+    /// Counters are kept as a native `i64`, so only the first increment of a value set as text parses it.
+    pub fn incr_by(&self, key: String, delta: i64) -> Result<i64, &'static str> {
+        self.expire_if_needed(&key);
+        let value = match self.map.entry(key.clone()) {
+            Entry::Occupied(mut e) => {
+                let current = match e.get() {
+                    RespFrame::Integer(v) => *v,
+                    RespFrame::BulkString(v) => std::str::from_utf8(v.as_ref())
+                        .ok()
+                        .and_then(|v| v.parse::<i64>().ok())
+                        .ok_or("ERR value is not an integer or out of range")?,
+                    _ => return Err("ERR value is not an integer or out of range"),
+                };
+                let value = current
+                    .checked_add(delta)
+                    .ok_or("ERR increment or decrement would overflow")?;
+                *e.get_mut() = RespFrame::Integer(value);
+                value
+            }
+            Entry::Vacant(e) => {
+                e.insert(RespFrame::Integer(delta));
+                delta
+            }
+        };
+        self.record_access(&key);
+        self.evict_if_needed(&key);
+        self.notify(&key, KeyOp::Set);
+        Ok(value)
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+        self.expire_if_needed(key);
+        let hmap = self.hmap.get(key)?;
+        self.record_access(key);
+        let value = hmap.get(field).map(|v| v.value().clone());
+        value
     }
 
     pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
+        // an expired hash must not lend its stale fields and deadline to the new field
+        self.expire_if_needed(&key);
+        let hmap = self.hmap.entry(key.clone()).or_default();
         hmap.insert(field, value);
+        drop(hmap);
+        self.record_access(&key);
+        self.evict_if_needed(&key);
+        self.notify(&key, KeyOp::HSet);
+    }
+
+    pub fn hexists(&self, key: &str, field: &str) -> bool {
+        self.expire_if_needed(key);
+        self.hmap
+            .get(key)
+            .map(|v| v.contains_key(field))
+            .unwrap_or(false)
+    }
+
+    /// Set a hash field only if it is absent, return whether the field was set.
+    pub fn hsetnx(&self, key: String, field: String, value: RespFrame) -> bool {
+        self.expire_if_needed(&key);
+        let hmap = self.hmap.entry(key.clone()).or_default();
+        let ret = match hmap.entry(field) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(e) => {
+                e.insert(value);
+                true
+            }
+        };
+        drop(hmap);
+        self.record_access(&key);
+        if ret {
+            self.evict_if_needed(&key);
+            self.notify(&key, KeyOp::HSet);
+        }
+        ret
+    }
+
+    /// Read-modify-write a hash field under the field lock, the field is left untouched when `f` fails.
+    pub fn hupdate<E>(
+        &self,
+        key: String,
+        field: String,
+        f: impl FnOnce(Option<&RespFrame>) -> Result<RespFrame, E>,
+    ) -> Result<RespFrame, E> {
+        self.expire_if_needed(&key);
+        let hmap = self.hmap.entry(key.clone()).or_default();
+        let ret = match hmap.entry(field) {
+            Entry::Occupied(mut e) => f(Some(e.get())).inspect(|v| {
+                e.insert(v.clone());
+            }),
+            Entry::Vacant(e) => f(None).inspect(|v| {
+                e.insert(v.clone());
+            }),
+        };
+        drop(hmap);
+        match ret {
+            Ok(_) => {
+                self.record_access(&key);
+                self.evict_if_needed(&key);
+                self.notify(&key, KeyOp::HSet);
+            }
+            // do not leave an empty hash behind when the first update failed
+            Err(_) => {
+                self.hmap.remove_if(&key, |_, v| v.is_empty());
+            }
+        }
+        ret
     }
 
     pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+        self.expire_if_needed(key);
+        let hmap = self.hmap.get(key).map(|v| v.clone());
+        if hmap.is_some() {
+            self.record_access(key);
+        }
+        hmap
+    }
+
+    /// Type of the value stored at key, as reported by TYPE.
+    pub fn key_type(&self, key: &str) -> Option<&'static str> {
+        self.expire_if_needed(key);
+        if self.map.contains_key(key) {
+            Some("string")
+        } else if self.hmap.contains_key(key) {
+            Some("hash")
+        } else {
+            None
+        }
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        self.map.contains_key(key) || self.hmap.contains_key(key)
+    }
+
+    /// Sorted snapshot of every live key, shared by the key iteration commands.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .map
+            .iter()
+            .map(|e| e.key().clone())
+            .chain(self.hmap.iter().map(|e| e.key().clone()))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys.retain(|key| self.exists(key));
+        keys
+    }
+
+    /// Number of keys held, like redis this includes expired keys not reclaimed yet.
+    pub fn dbsize(&self) -> usize {
+        self.map.len() + self.hmap.len()
+    }
+
+    pub fn random_key(&self) -> Option<String> {
+        self.keys().choose(&mut rand::thread_rng()).cloned()
+    }
+
+    /// Duplicate the value and expiry stored at source into destination.
+    pub fn copy(&self, source: &str, destination: &str, replace: bool) -> bool {
+        if !self.exists(source) || source == destination {
+            return false;
+        }
+        if self.exists(destination) {
+            if !replace {
+                return false;
+            }
+            self.del(destination);
+        }
+
+        if let Some(value) = self.get(source) {
+            self.map.insert(destination.to_string(), value);
+            self.notify(destination, KeyOp::Set);
+        } else if let Some(hmap) = self.hgetall(source) {
+            self.hmap.insert(destination.to_string(), hmap);
+            self.notify(destination, KeyOp::HSet);
+        } else {
+            return false;
+        }
+        if let Some(deadline) = self.expires.get(source).map(|v| *v) {
+            self.expires.insert(destination.to_string(), deadline);
+            self.notify(destination, KeyOp::Expire);
+        }
+        self.record_access(destination);
+        self.evict_if_needed(destination);
+        true
+    }
+
+    /// Internal encoding name of the value stored at key, as reported by OBJECT ENCODING.
+    pub fn encoding(&self, key: &str) -> Option<&'static str> {
+        // inspecting a key is not an access, like the NOTOUCH lookups of OBJECT in redis
+        self.expire_if_needed(key);
+        if let Some(value) = self.map.get(key) {
+            let encoding = match value.value() {
+                RespFrame::Integer(_) => "int",
+                RespFrame::BulkString(s) => match std::str::from_utf8(s.as_ref()) {
+                    Ok(v) if v.len() <= 20 && v.parse::<i64>().is_ok() => "int",
+                    _ if s.as_ref().len() <= EMBSTR_SIZE_LIMIT => "embstr",
+                    _ => "raw",
+                },
+                _ => "raw",
+            };
+            return Some(encoding);
+        }
+        self.hmap.contains_key(key).then_some("hashtable")
+    }
+
+    /// The value stored at key without counting as an access, a hash is returned as a map.
+    pub fn peek(&self, key: &str) -> Option<RespFrame> {
+        self.expire_if_needed(key);
+        if let Some(value) = self.map.get(key) {
+            return Some(render(value.value().clone()));
+        }
+        self.hmap.get(key).map(|fields| {
+            let mut map = RespMap::new();
+            for field in fields.iter() {
+                map.insert(field.key().clone(), field.value().clone());
+            }
+            map.into()
+        })
+    }
+
+    /// Time since the key was last read or written, `None` if it does not exist.
+    pub fn idle_time(&self, key: &str) -> Option<Duration> {
+        if !self.exists(key) {
+            return None;
+        }
+        let idle = self
+            .access
+            .get(key)
+            .map(|accessed| accessed.elapsed())
+            .unwrap_or_default();
+        Some(idle)
+    }
+
+    /// Mark a key as accessed without reading its value, return whether it exists.
+    pub fn touch(&self, key: &str) -> bool {
+        let exists = self.exists(key);
+        if exists {
+            self.record_access(key);
+        }
+        exists
+    }
+
+    pub fn del(&self, key: &str) -> bool {
+        self.expires.remove(key);
+        self.access.remove(key);
+        let removed = self.map.remove(key).is_some();
+        let removed = self.hmap.remove(key).is_some() || removed;
+        if removed {
+            self.notify(key, KeyOp::Del);
+        }
+        removed
+    }
+
+    /// Remove a key right away but defer freeing large values to a background task.
+    pub fn unlink(&self, key: &str) -> bool {
+        self.expires.remove(key);
+        self.access.remove(key);
+        let removed = self.map.remove(key).is_some();
+        let removed = match self.hmap.remove(key) {
+            Some((_, hmap)) => {
+                if hmap.len() > LAZYFREE_THRESHOLD {
+                    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+                        handle.spawn(async move { drop(hmap) });
+                    }
+                }
+                true
+            }
+            None => removed,
+        };
+        if removed {
+            self.notify(key, KeyOp::Del);
+        }
+        removed
+    }
+
+    /// Set the deadline of an existing key, return false if the key does not exist.
+    pub fn expire(&self, key: &str, deadline: Instant) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        self.expires.insert(key.to_string(), deadline);
+        self.notify(key, KeyOp::Expire);
+        true
+    }
+
+    /// Drop the expiry of a key, return whether it had one.
+    pub fn persist(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        self.expires.remove(key).is_some()
+    }
+
+    /// Remaining time to live of a key, `None` if the key has no associated expiry.
+    pub fn ttl(&self, key: &str) -> Option<Duration> {
+        self.expire_if_needed(key);
+        self.expires
+            .get(key)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Absolute expiry of a key in unix milliseconds, `None` if the key has no associated expiry.
+    pub fn expire_time(&self, key: &str) -> Option<i64> {
+        self.expire_if_needed(key);
+        let deadline = *self.expires.get(key)?;
+        Some(self.unix_millis_of(deadline))
+    }
+
+    /// The unix timestamp in milliseconds of a deadline, the inverse of `deadline_at`.
+    pub fn unix_millis_of(&self, deadline: Instant) -> i64 {
+        let (instant, system) = self.clock;
+        let unix = match deadline.checked_duration_since(instant) {
+            Some(elapsed) => system + elapsed,
+            None => system - instant.duration_since(deadline),
+        };
+        unix_millis(unix)
+    }
+
+    /// The deadline matching a unix timestamp in milliseconds, the inverse of `expire_time`.
+    pub fn deadline_at(&self, unix_milliseconds: i64) -> Instant {
+        let (instant, system) = self.clock;
+        let offset = unix_milliseconds - unix_millis(system);
+        if offset >= 0 {
+            instant + Duration::from_millis(offset as u64)
+        } else {
+            instant
+                .checked_sub(Duration::from_millis(offset.unsigned_abs()))
+                .unwrap_or(instant)
+        }
+    }
+
+    /// Count one execution of `name` that took `elapsed`.
+    pub fn record_command(&self, name: &'static str, elapsed: Duration) {
+        let mut stats = self.stats.entry(name).or_default();
+        stats.calls += 1;
+        stats.usec += elapsed.as_micros() as u64;
+    }
+
+    /// Counters of every command executed so far, sorted by name.
+    pub fn command_stats(&self) -> Vec<(&'static str, CommandStats)> {
+        let mut stats = self
+            .stats
+            .iter()
+            .map(|e| (*e.key(), *e.value()))
+            .collect::<Vec<_>>();
+        stats.sort_by_key(|(name, _)| *name);
+        stats
+    }
+
+    /// Receive an event for every write applied to the keyspace from now on.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<KeyEvent> {
+        self.events.subscribe()
+    }
+
+    /// Receive a line for every command executed from now on, used by MONITOR.
+    pub fn subscribe_monitor(&self) -> broadcast::Receiver<String> {
+        self.monitor.subscribe()
+    }
+
+    /// Whether any MONITOR client is listening, so formatting can be skipped otherwise.
+    pub fn is_monitored(&self) -> bool {
+        self.monitor.receiver_count() > 0
+    }
+
+    pub fn publish_monitor(&self, line: String) {
+        let _ = self.monitor.send(line);
+    }
+
+    /// Receive every write command executed from now on, used to feed a replica.
+    pub fn subscribe_replication(&self) -> broadcast::Receiver<RespFrame> {
+        self.replication.subscribe()
+    }
+
+    /// Whether any replica is connected, so requests are only kept around when needed.
+    pub fn has_replicas(&self) -> bool {
+        self.replication.receiver_count() > 0
+    }
+
+    pub fn propagate(&self, command: RespFrame) {
+        let _ = self.replication.send(command);
+    }
+
+    /// Run a write, and stream the command `propagated` builds to the replicas unless it fails.
+    ///
+    /// A replica syncing concurrently sees the write either in its snapshot or in its stream,
+    /// and replicas receive writes in the order they were applied.
+    pub fn execute_write(
+        &self,
+        execute: impl FnOnce() -> RespFrame,
+        propagated: impl FnOnce() -> RespFrame,
+    ) -> RespFrame {
+        let _barrier = self.barrier.read().unwrap();
+        if !self.has_replicas() {
+            return execute();
+        }
+        let _order = self.write_order.lock().unwrap();
+        let reply = execute();
+        if !matches!(reply, RespFrame::SimpleError(_)) {
+            self.propagate(propagated());
+        }
+        reply
+    }
+
+    /// Run a command that is not a write, never while the keyspace is being rebuilt.
+    pub fn execute_read(&self, execute: impl FnOnce() -> RespFrame) -> RespFrame {
+        let _barrier = self.barrier.read().unwrap();
+        execute()
+    }
+
+    /// The data set of a new replica, and every write executed after it was taken.
+    pub fn sync_replica(&self) -> (BackendSnapshot, broadcast::Receiver<RespFrame>) {
+        let _barrier = self.barrier.write().unwrap();
+        (self.snapshot(), self.subscribe_replication())
+    }
+
+    /// Follow a primary with the task `spawn` starts, replacing the one following before.
+    pub fn follow(&self, spawn: impl FnOnce() -> AbortHandle) {
+        let mut primary = self.primary.lock().unwrap();
+        // spawned under the lock, so the task cannot end before it is registered
+        if let Some(previous) = primary.replace(spawn()) {
+            previous.abort();
+        }
+    }
+
+    /// Stop following the primary, this instance becomes a primary again.
+    pub fn unfollow(&self) {
+        if let Some(previous) = self.primary.lock().unwrap().take() {
+            previous.abort();
+        }
+    }
+
+    /// Called by the task following a primary when the stream ends on its own.
+    pub fn follow_ended(&self, task: Id) {
+        let mut primary = self.primary.lock().unwrap();
+        // a REPLICAOF may have replaced the task in the meantime
+        if primary.as_ref().is_some_and(|handle| handle.id() == task) {
+            *primary = None;
+        }
+    }
+
+    /// Whether this instance follows a primary.
+    pub fn is_replica(&self) -> bool {
+        self.primary.lock().unwrap().is_some()
+    }
+
+    /// A unique id for a new client connection.
+    pub fn next_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Drop every key, used by a replica before it loads the data of its primary.
+    pub fn flush(&self) {
+        let _barrier = self.barrier.write().unwrap();
+        self.map.clear();
+        self.hmap.clear();
+        self.expires.clear();
+        self.access.clear();
+    }
+
+    // sending only fails when nobody is subscribed, which is fine
+    fn notify(&self, key: &str, op: KeyOp) {
+        let _ = self.events.send(KeyEvent::new(key, op));
+    }
+
+    /// Spawn a task that periodically removes expired keys which are never accessed again.
+    /// The task stops once every handle to the backend has been dropped.
+    pub fn start_expiry_cycle(&self, interval: Duration) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.0);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match Weak::upgrade(&inner) {
+                    Some(inner) => Backend(inner).active_expire_cycle(),
+                    None => break,
+                }
+            }
+        })
+    }
+
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.active_expire.store(enabled, Ordering::Relaxed);
+    }
+
+    // remove expired keys in bounded batches so no shard lock is held for long
+    fn active_expire_cycle(&self) {
+        if !self.active_expire.load(Ordering::Relaxed) {
+            return;
+        }
+        loop {
+            let now = Instant::now();
+            let expired: Vec<String> = self
+                .expires
+                .iter()
+                .filter(|e| *e.value() <= now)
+                .take(EXPIRE_CYCLE_BATCH)
+                .map(|e| e.key().clone())
+                .collect();
+            for key in expired.iter() {
+                self.expire_if_needed(key);
+            }
+            if expired.len() < EXPIRE_CYCLE_BATCH {
+                break;
+            }
+        }
+    }
+
+    // lazy expiration: drop the key once its deadline has passed
+    fn expire_if_needed(&self, key: &str) {
+        let expired = self
+            .expires
+            .remove_if(key, |_, deadline| *deadline <= Instant::now())
+            .is_some();
+        if expired {
+            self.map.remove(key);
+            self.hmap.remove(key);
+            self.access.remove(key);
+            self.notify(key, KeyOp::Expired);
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+// counters are stored as native integers but read back as the string they stand for
+fn render(value: RespFrame) -> RespFrame {
+    match value {
+        RespFrame::Integer(v) => BulkString::new(v.to_string()).into(),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Backend, BackendConfig, EvictionConfig, EvictionPolicy, KeyEvent, KeyOp};
+    use crate::{BulkString, RespFrame};
+
+    #[test]
+    fn test_write_events() {
+        let backend = Backend::default();
+        let mut events = backend.subscribe_events();
+
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        backend.expire("foo", Instant::now());
+        assert!(!backend.exists("foo"));
+        backend.expire("h", Instant::now() + Duration::from_secs(100));
+        assert!(backend.copy("h", "h2", false));
+        assert!(backend.del("h"));
+        // deleting a missing key is not a write
+        assert!(!backend.del("h"));
+        backend.set("s".to_string(), BulkString::new(b"v").into());
+        assert!(backend.copy("s", "h2", true));
+
+        let expected = [
+            KeyEvent::new("foo", KeyOp::Set),
+            KeyEvent::new("h", KeyOp::HSet),
+            KeyEvent::new("foo", KeyOp::Expire),
+            KeyEvent::new("foo", KeyOp::Expired),
+            KeyEvent::new("h", KeyOp::Expire),
+            KeyEvent::new("h2", KeyOp::HSet),
+            KeyEvent::new("h2", KeyOp::Expire),
+            KeyEvent::new("h", KeyOp::Del),
+            KeyEvent::new("s", KeyOp::Set),
+            KeyEvent::new("h2", KeyOp::Del),
+            KeyEvent::new("h2", KeyOp::Set),
+        ];
+        for event in expected {
+            assert_eq!(events.try_recv().unwrap(), event);
+        }
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_idle_time() {
+        let backend = Backend::default();
+        assert_eq!(backend.idle_time("foo"), None);
+
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        assert!(backend.idle_time("foo").unwrap() < Duration::from_secs(1));
+
+        // pretend the key was last used a while ago
+        backend
+            .access
+            .insert("foo".to_string(), Instant::now() - Duration::from_secs(5));
+        assert!(backend.idle_time("foo").unwrap() >= Duration::from_secs(5));
+
+        // inspecting the key leaves its idle time alone
+        assert_eq!(backend.encoding("foo"), Some("embstr"));
+        assert!(backend.idle_time("foo").unwrap() >= Duration::from_secs(5));
+
+        assert!(backend.get("foo").is_some());
+        assert!(backend.idle_time("foo").unwrap() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_new_from_config() {
+        let config = BackendConfig {
+            eviction: Some(EvictionConfig {
+                max_keys: 2,
+                policy: EvictionPolicy::AllKeysRandom,
+            }),
+            databases: 4,
+            requirepass: Some("secret".to_string()),
+            timeout: Some(Duration::from_secs(30)),
+            appendonly: true,
+            ..Default::default()
+        };
+        let backend = Backend::new(config.clone());
+        assert_eq!(backend.config(), &config);
+        assert_eq!(Backend::default().config(), &BackendConfig::default());
+
+        // the eviction settings are the ones in effect
+        for key in ["a", "b", "c"] {
+            backend.set(key.to_string(), BulkString::new(b"v").into());
+        }
+        assert_eq!(backend.dbsize(), 2);
+    }
+
+    #[test]
+    fn test_with_string_mut_concurrent_append() {
+        let backend = Backend::default();
+        assert_eq!(
+            backend.with_string_mut("k".to_string(), false, |v| v.len()),
+            None
+        );
+        assert!(!backend.exists("k"));
+
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        backend.with_string_mut("k".to_string(), true, |v| v.push(b'x'));
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            backend.get("k"),
+            Some(BulkString::new(vec![b'x'; 800]).into())
+        );
+
+        // counters are handed over as their text
+        backend.incr_by("n".to_string(), 12).unwrap();
+        let len = backend.with_string_mut("n".to_string(), false, |v| {
+            v.extend_from_slice(b"34");
+            v.len()
+        });
+        assert_eq!(len, Some(4));
+        assert_eq!(backend.get("n"), Some(BulkString::new(b"1234").into()));
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let backend = Backend::with_capacity(3, 1024);
+        assert!(backend.map.capacity() >= 1024);
+
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        assert_eq!(backend.get("foo"), Some(BulkString::new(b"bar").into()));
+    }
+
+    // the tokio clock is paused, so the ticks are driven by the runtime rather than wall time
+    #[tokio::test(start_paused = true)]
+    async fn test_active_expiry_cycle() {
+        let backend = Backend::default();
+        for i in 0..50 {
+            let key = format!("key{}", i);
+            backend.set(key.clone(), RespFrame::BulkString(BulkString::new(b"v")));
+            // already due, whatever the load of the machine running the test
+            backend.expire(&key, Instant::now());
+        }
+        backend.set(
+            "persistent".to_string(),
+            RespFrame::BulkString(BulkString::new(b"v")),
+        );
+
+        let handle = backend.start_expiry_cycle(Duration::from_millis(5));
+        for _ in 0..10 {
+            tokio::time::advance(Duration::from_millis(5)).await;
+        }
+
+        // never read the keys, they must have been reclaimed by the cycle
+        assert_eq!(backend.map.len(), 1);
+        assert!(backend.expires.is_empty());
+        handle.abort();
     }
 }
@@ -0,0 +1,90 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::RespArray;
+
+use super::Backend;
+
+// same defaults as slowlog-log-slower-than and slowlog-max-len in redis
+const DEFAULT_THRESHOLD: Duration = Duration::from_millis(10);
+const DEFAULT_MAX_LEN: usize = 128;
+
+/// A command that ran longer than the slowlog threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowLogEntry {
+    pub id: u64,
+    // unix time in seconds the command was logged at
+    pub timestamp: u64,
+    pub duration: Duration,
+    pub args: RespArray,
+}
+
+#[derive(Debug)]
+pub(crate) struct SlowLog {
+    // newest first, never longer than max_len
+    entries: VecDeque<SlowLogEntry>,
+    next_id: u64,
+    threshold: Option<Duration>,
+    max_len: usize,
+}
+
+impl Default for SlowLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            next_id: 0,
+            threshold: Some(DEFAULT_THRESHOLD),
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+}
+
+impl Backend {
+    /// Log commands running longer than `threshold`, `None` disables the slowlog.
+    pub fn set_slowlog_threshold(&self, threshold: Option<Duration>) {
+        self.slowlog.lock().unwrap().threshold = threshold;
+    }
+
+    pub fn is_slow(&self, elapsed: Duration) -> bool {
+        self.slowlog
+            .lock()
+            .unwrap()
+            .threshold
+            .is_some_and(|threshold| elapsed > threshold)
+    }
+
+    /// Record a slow command, dropping the oldest entry once the log is full.
+    pub fn record_slow_command(&self, args: RespArray, duration: Duration) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut slowlog = self.slowlog.lock().unwrap();
+        let entry = SlowLogEntry {
+            id: slowlog.next_id,
+            timestamp,
+            duration,
+            args,
+        };
+        slowlog.next_id += 1;
+        slowlog.entries.push_front(entry);
+        let max_len = slowlog.max_len;
+        slowlog.entries.truncate(max_len);
+    }
+
+    /// Up to `count` entries, newest first.
+    pub fn slowlog(&self, count: usize) -> Vec<SlowLogEntry> {
+        let slowlog = self.slowlog.lock().unwrap();
+        slowlog.entries.iter().take(count).cloned().collect()
+    }
+
+    pub fn slowlog_len(&self) -> usize {
+        self.slowlog.lock().unwrap().entries.len()
+    }
+
+    pub fn reset_slowlog(&self) {
+        self.slowlog.lock().unwrap().entries.clear();
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, time::Instant};
+
+use crate::RespFrame;
+
+use super::{render, Backend};
+
+/// Owned point-in-time copy of the keyspace, detached from the live maps.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackendSnapshot {
+    pub strings: HashMap<String, RespFrame>,
+    pub hashes: HashMap<String, HashMap<String, RespFrame>>,
+    // deadlines of the copied keys that have one
+    pub expires: HashMap<String, Instant>,
+}
+
+impl Backend {
+    /// Copy every live key out of the backend, shard by shard.
+    ///
+    /// Only one shard lock is held at a time, so the copy is consistent per key,
+    /// and no lock is held once it is returned.
+    pub fn snapshot(&self) -> BackendSnapshot {
+        let now = Instant::now();
+        let expires = self
+            .expires
+            .iter()
+            .filter(|e| *e.value() > now)
+            .map(|e| (e.key().clone(), *e.value()))
+            .collect::<HashMap<_, _>>();
+        let expired = |key: &str| self.expires.contains_key(key) && !expires.contains_key(key);
+
+        let strings = self
+            .map
+            .iter()
+            .filter(|e| !expired(e.key()))
+            .map(|e| (e.key().clone(), render(e.value().clone())))
+            .collect();
+        let hashes = self
+            .hmap
+            .iter()
+            .filter(|e| !expired(e.key()))
+            .map(|e| {
+                let fields = e
+                    .value()
+                    .iter()
+                    .map(|f| (f.key().clone(), f.value().clone()))
+                    .collect();
+                (e.key().clone(), fields)
+            })
+            .collect();
+
+        BackendSnapshot {
+            strings,
+            hashes,
+            expires,
+        }
+    }
+
+    /// Replace the whole keyspace with what `rebuild` makes of a snapshot of it.
+    ///
+    /// Commands served to connections wait until the new keyspace is in place, so they
+    /// never see a mix of old and new keys and no write lands in between to survive it.
+    pub fn rebuild<E>(
+        &self,
+        rebuild: impl FnOnce(BackendSnapshot) -> Result<BackendSnapshot, E>,
+    ) -> Result<(), E> {
+        let _barrier = self.barrier.write().unwrap();
+        let BackendSnapshot {
+            strings,
+            hashes,
+            expires,
+        } = rebuild(self.snapshot())?;
+
+        self.map.clear();
+        self.hmap.clear();
+        self.expires.clear();
+        // idle times carry over to the keys that are still there
+        self.access
+            .retain(|key, _| strings.contains_key(key) || hashes.contains_key(key));
+        for (key, deadline) in expires {
+            self.expires.insert(key, deadline);
+        }
+        for (key, value) in strings {
+            self.map.insert(key, value);
+        }
+        for (key, fields) in hashes {
+            self.hmap.insert(key, fields.into_iter().collect());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{Backend, BulkString};
+
+    #[test]
+    fn test_snapshot_is_detached() {
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        backend.set("gone".to_string(), BulkString::new(b"x").into());
+        backend.expires.insert("gone".to_string(), Instant::now());
+        backend.expire("foo", Instant::now() + Duration::from_secs(60));
+
+        let snapshot = backend.snapshot();
+
+        backend.set("foo".to_string(), BulkString::new(b"changed").into());
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"changed").into(),
+        );
+        backend.del("foo");
+        backend.set("new".to_string(), BulkString::new(b"v").into());
+
+        assert_eq!(snapshot.strings.len(), 1);
+        assert_eq!(snapshot.strings["foo"], BulkString::new(b"bar").into());
+        assert_eq!(snapshot.hashes["h"]["f"], BulkString::new(b"v").into());
+        assert!(snapshot.expires.contains_key("foo"));
+        assert!(!snapshot.expires.contains_key("gone"));
+    }
+
+    #[test]
+    fn test_rebuild_replaces_the_keyspace() -> Result<(), ()> {
+        let backend = Backend::default();
+        backend.set("kept".to_string(), BulkString::new(b"v").into());
+        backend.set("dropped".to_string(), BulkString::new(b"v").into());
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+
+        backend.rebuild(|mut snapshot| {
+            snapshot.strings.remove("dropped");
+            snapshot.strings.remove("h");
+            // the key changes type, it is no longer a hash afterwards
+            let h = snapshot.hashes.remove("h").ok_or(())?;
+            snapshot.strings.insert("h".to_string(), h["f"].clone());
+            snapshot
+                .expires
+                .insert("kept".to_string(), Instant::now() + Duration::from_secs(60));
+            Ok(snapshot)
+        })?;
+
+        assert_eq!(backend.get("kept"), Some(BulkString::new(b"v").into()));
+        assert!(backend.ttl("kept").is_some());
+        assert_eq!(backend.get("dropped"), None);
+        assert_eq!(backend.get("h"), Some(BulkString::new(b"v").into()));
+        assert!(backend.hgetall("h").is_none());
+
+        // a failed rebuild leaves the keyspace alone
+        assert_eq!(backend.rebuild(|_| Err(())), Err(()));
+        assert_eq!(backend.get("kept"), Some(BulkString::new(b"v").into()));
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+/// Per command counters reported by `INFO commandstats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStats {
+    pub calls: u64,
+    // cumulative execution time in microseconds
+    pub usec: u64,
+}
+
+impl CommandStats {
+    pub fn usec_per_call(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.usec as f64 / self.calls as f64
+    }
+}
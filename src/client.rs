@@ -0,0 +1,96 @@
+use anyhow::{anyhow, Result};
+use futures::SinkExt as _;
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio_stream::StreamExt as _;
+use tokio_util::codec::Framed;
+
+use crate::{network::RespFrameCodec, RespArray, RespFrame};
+
+/// A minimal async client speaking RESP to the server.
+#[derive(Debug)]
+pub struct Client {
+    framed: Framed<TcpStream, RespFrameCodec>,
+}
+
+impl Client {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        let mut client = Self {
+            framed: Framed::new(stream, RespFrameCodec::default()),
+        };
+        // replies are decoded as RESP3, so ask the server for it
+        if let RespFrame::SimpleError(e) = client.command(&[b"hello", b"3"]).await? {
+            return Err(anyhow!("handshake failed: {}", e));
+        }
+        Ok(client)
+    }
+
+    /// Send a raw command made of bulk string arguments and wait for its reply.
+    pub async fn command(&mut self, args: &[&[u8]]) -> Result<RespFrame> {
+        self.framed.send(RespArray::command(args).into()).await?;
+        match self.framed.next().await {
+            Some(frame) => frame,
+            None => Err(anyhow!("connection closed by server")),
+        }
+    }
+
+    pub async fn get(&mut self, key: &str) -> Result<RespFrame> {
+        self.command(&[b"get", key.as_bytes()]).await
+    }
+
+    pub async fn set(&mut self, key: &str, value: impl AsRef<[u8]>) -> Result<RespFrame> {
+        self.command(&[b"set", key.as_bytes(), value.as_ref()])
+            .await
+    }
+
+    pub async fn hget(&mut self, key: &str, field: &str) -> Result<RespFrame> {
+        self.command(&[b"hget", key.as_bytes(), field.as_bytes()])
+            .await
+    }
+
+    pub async fn hset(
+        &mut self,
+        key: &str,
+        field: &str,
+        value: impl AsRef<[u8]>,
+    ) -> Result<RespFrame> {
+        self.command(&[b"hset", key.as_bytes(), field.as_bytes(), value.as_ref()])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use tokio::net::TcpListener;
+
+    use super::Client;
+    use crate::{network, Backend, BulkString, RespFrame, RespNull, SimpleString};
+
+    #[tokio::test]
+    async fn test_client_round_trip() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(network::serve(
+            listener,
+            Backend::default(),
+            Default::default(),
+        ));
+
+        let ok: RespFrame = SimpleString::new("OK".to_string()).into();
+        let mut client = Client::connect(addr).await?;
+        assert_eq!(client.set("hello", "world").await?, ok);
+        assert_eq!(client.get("hello").await?, BulkString::new(b"world").into());
+        assert_eq!(client.get("missing").await?, RespFrame::Null(RespNull));
+
+        assert_eq!(client.hset("map", "foo", b"bar").await?, ok);
+        assert_eq!(
+            client.hget("map", "foo").await?,
+            BulkString::new(b"bar").into()
+        );
+
+        let reply = client.command(&[b"hexists", b"map", b"foo"]).await?;
+        assert_eq!(reply, RespFrame::Integer(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+use crate::{backend::Backend, BulkString, RespArray, RespFrame};
+
+use super::{
+    ensure_string, extract_args, map::normalize_index, validate_command, validate_command_range,
+    Command, CommandEntry, CommandError, CommandExecutor,
+};
+
+// strings are capped at 512MB like redis, so offsets must fit in 2^32 bits
+const MAX_BIT_OFFSET: u64 = (512 * 1024 * 1024 * 8) - 1;
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("setbit", true, |v| v.try_into().map(Command::SetBit)),
+    ("getbit", false, |v| v.try_into().map(Command::GetBit)),
+    ("bitcount", false, |v| v.try_into().map(Command::BitCount)),
+    ("bitop", true, |v| v.try_into().map(Command::BitOp)),
+];
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct SetBit {
+    key: String,
+    offset: u64,
+    value: bool,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct GetBit {
+    key: String,
+    offset: u64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct BitCount {
+    key: String,
+    // inclusive byte range, the whole string when absent
+    range: Option<(i64, i64)>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct BitOp {
+    op: BitOperation,
+    destination: String,
+    sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum BitOperation {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl CommandExecutor for SetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        let byte = (self.offset / 8) as usize;
+        // bit 0 is the most significant bit of the first byte
+        let mask = 0x80u8 >> (self.offset % 8);
+        let old = backend.with_string_mut(self.key, true, |bytes| {
+            if bytes.len() <= byte {
+                bytes.resize(byte + 1, 0);
+            }
+            let old = bytes[byte] & mask != 0;
+            if self.value {
+                bytes[byte] |= mask;
+            } else {
+                bytes[byte] &= !mask;
+            }
+            old
+        });
+        RespFrame::Integer(old.unwrap_or_default() as i64)
+    }
+}
+
+impl CommandExecutor for GetBit {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let bytes = match string_bytes(backend, &self.key) {
+            Ok(bytes) => bytes,
+            Err(e) => return e,
+        };
+        let bit = bytes
+            .get((self.offset / 8) as usize)
+            .map(|b| b & (0x80 >> (self.offset % 8)) != 0)
+            .unwrap_or(false);
+        RespFrame::Integer(bit as i64)
+    }
+}
+
+impl CommandExecutor for BitCount {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let bytes = match string_bytes(backend, &self.key) {
+            Ok(bytes) => bytes,
+            Err(e) => return e,
+        };
+        let len = bytes.len() as i64;
+        let (start, end) = match self.range {
+            // both ends are clamped into the string like redis does
+            Some((start, end)) => (
+                normalize_index(start, len).max(0),
+                normalize_index(end, len).max(0).min(len - 1),
+            ),
+            None => (0, len - 1),
+        };
+        if len == 0 || start > end {
+            return RespFrame::Integer(0);
+        }
+        let count: u32 = bytes[start as usize..=end as usize]
+            .iter()
+            .map(|b| b.count_ones())
+            .sum();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl CommandExecutor for BitOp {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let mut operands = Vec::with_capacity(self.sources.len());
+        for key in self.sources.iter() {
+            match string_bytes(backend, key) {
+                Ok(bytes) => operands.push(bytes),
+                Err(e) => return e,
+            }
+        }
+        // shorter operands are zero extended to the longest one
+        let len = operands.iter().map(Vec::len).max().unwrap_or(0);
+        let byte = |operand: &Vec<u8>, i: usize| operand.get(i).copied().unwrap_or(0);
+        let result = (0..len)
+            .map(|i| match self.op {
+                BitOperation::And => operands.iter().fold(0xff, |acc, o| acc & byte(o, i)),
+                BitOperation::Or => operands.iter().fold(0, |acc, o| acc | byte(o, i)),
+                BitOperation::Xor => operands.iter().fold(0, |acc, o| acc ^ byte(o, i)),
+                BitOperation::Not => !byte(&operands[0], i),
+            })
+            .collect::<Vec<u8>>();
+        // an empty result removes the destination like redis does
+        if result.is_empty() {
+            backend.del(&self.destination);
+        } else {
+            backend.set(self.destination, BulkString::new(result).into());
+        }
+        RespFrame::Integer(len as i64)
+    }
+}
+
+// raw bytes of a string value, missing keys read as an empty string
+fn string_bytes(backend: &Backend, key: &str) -> Result<Vec<u8>, RespFrame> {
+    ensure_string(backend, key)?;
+    // counters included, every string value is read back as a bulk string
+    match backend.get(key) {
+        Some(RespFrame::BulkString(v)) => Ok(v.as_ref().to_vec()),
+        _ => Ok(vec![]),
+    }
+}
+
+fn parse_offset(frame: &RespFrame) -> Result<u64, CommandError> {
+    match i64::try_from(frame) {
+        Ok(offset) if (0..=MAX_BIT_OFFSET as i64).contains(&offset) => Ok(offset as u64),
+        _ => Err(CommandError::InvalidArgument(
+            "bit offset is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setbit"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(offset), Some(bit)) => Ok(SetBit {
+                key: key.try_into()?,
+                offset: parse_offset(&offset)?,
+                value: match i64::try_from(&bit) {
+                    Ok(0) => false,
+                    Ok(1) => true,
+                    _ => {
+                        return Err(CommandError::InvalidArgument(
+                            "bit is not an integer or out of range".to_string(),
+                        ))
+                    }
+                },
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getbit"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(offset)) => Ok(GetBit {
+                key: key.try_into()?,
+                offset: parse_offset(&offset)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BitCount {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["bitcount"], 1..=3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), None, None) => Ok(BitCount {
+                key: key.try_into()?,
+                range: None,
+            }),
+            (Some(key), Some(start), Some(end)) => Ok(BitCount {
+                key: key.try_into()?,
+                range: Some((i64::try_from(&start)?, i64::try_from(&end)?)),
+            }),
+            _ => Err(CommandError::InvalidArgument("syntax error".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for BitOp {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["bitop"], 3..)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let op = match args.next() {
+            Some(RespFrame::BulkString(op)) => match op.as_ref().to_ascii_lowercase().as_slice() {
+                b"and" => BitOperation::And,
+                b"or" => BitOperation::Or,
+                b"xor" => BitOperation::Xor,
+                b"not" => BitOperation::Not,
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            },
+            _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+        };
+        let destination = match args.next() {
+            Some(key) => key.try_into()?,
+            None => return Err(CommandError::WrongArity("bitop".to_string())),
+        };
+        let sources = args.map(String::try_from).collect::<Result<Vec<_>, _>>()?;
+        if op == BitOperation::Not && sources.len() != 1 {
+            return Err(CommandError::InvalidArgument(
+                "BITOP NOT must be called with a single source key.".into(),
+            ));
+        }
+        Ok(BitOp {
+            op,
+            destination,
+            sources,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{BitCount, BitOp, GetBit, SetBit};
+    use crate::{backend::Backend, cmd::run, BulkString, RespArray, RespFrame, SimpleError};
+
+    #[test]
+    fn test_setbit_on_fresh_key() -> Result<()> {
+        let backend = Backend::default();
+        let ret = run::<SetBit>(&backend, &[b"setbit", b"k", b"7", b"1"])?;
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert_eq!(backend.get("k"), Some(BulkString::new(vec![0x01]).into()));
+
+        let ret = run::<SetBit>(&backend, &[b"setbit", b"k", b"7", b"0"])?;
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert_eq!(backend.get("k"), Some(BulkString::new(vec![0x00]).into()));
+
+        // growing the string pads with zero bytes
+        run::<SetBit>(&backend, &[b"setbit", b"k", b"17", b"1"])?;
+        assert_eq!(
+            backend.get("k"),
+            Some(BulkString::new(vec![0x00, 0x00, 0x40]).into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_getbit() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("k".to_string(), BulkString::new(b"`").into());
+        assert_eq!(
+            run::<GetBit>(&backend, &[b"getbit", b"k", b"1"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run::<GetBit>(&backend, &[b"getbit", b"k", b"3"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run::<GetBit>(&backend, &[b"getbit", b"k", b"100"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run::<GetBit>(&backend, &[b"getbit", b"nope", b"0"])?,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitcount() -> Result<()> {
+        let backend = Backend::default();
+        run::<SetBit>(&backend, &[b"setbit", b"k", b"7", b"1"])?;
+        assert_eq!(
+            run::<BitCount>(&backend, &[b"bitcount", b"k"])?,
+            RespFrame::Integer(1)
+        );
+
+        backend.set("foobar".to_string(), BulkString::new(b"foobar").into());
+        let bitcount = |start: &[u8], end: &[u8]| {
+            run::<BitCount>(&backend, &[b"bitcount", b"foobar", start, end])
+        };
+        assert_eq!(
+            run::<BitCount>(&backend, &[b"bitcount", b"foobar"])?,
+            RespFrame::Integer(26)
+        );
+        assert_eq!(bitcount(b"0", b"0")?, RespFrame::Integer(4));
+        assert_eq!(bitcount(b"1", b"1")?, RespFrame::Integer(6));
+        assert_eq!(bitcount(b"-2", b"-1")?, RespFrame::Integer(7));
+        assert_eq!(bitcount(b"5", b"1")?, RespFrame::Integer(0));
+        assert_eq!(bitcount(b"0", b"-100")?, RespFrame::Integer(4));
+        assert_eq!(bitcount(b"-100", b"100")?, RespFrame::Integer(26));
+        assert_eq!(
+            run::<BitCount>(&backend, &[b"bitcount", b"nope"])?,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bitop() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("a".to_string(), BulkString::new(vec![0xff, 0x0f]).into());
+        backend.set("b".to_string(), BulkString::new(vec![0x35]).into());
+
+        // b is zero extended to the length of a
+        assert_eq!(
+            run::<BitOp>(&backend, &[b"bitop", b"AND", b"dest", b"a", b"b"])?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(
+            backend.get("dest"),
+            Some(BulkString::new(vec![0x35, 0x00]).into())
+        );
+        run::<BitOp>(&backend, &[b"bitop", b"or", b"dest", b"a", b"b"])?;
+        assert_eq!(
+            backend.get("dest"),
+            Some(BulkString::new(vec![0xff, 0x0f]).into())
+        );
+        run::<BitOp>(&backend, &[b"bitop", b"xor", b"dest", b"a", b"b"])?;
+        assert_eq!(
+            backend.get("dest"),
+            Some(BulkString::new(vec![0xca, 0x0f]).into())
+        );
+
+        assert_eq!(
+            run::<BitOp>(&backend, &[b"bitop", b"not", b"dest", b"a"])?,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(
+            backend.get("dest"),
+            Some(BulkString::new(vec![0x00, 0xf0]).into())
+        );
+
+        // only missing sources leave nothing to store
+        assert_eq!(
+            run::<BitOp>(&backend, &[b"bitop", b"or", b"dest", b"nope"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(backend.get("dest"), None);
+        assert!(
+            BitOp::try_from(RespArray::command(&[b"bitop", b"not", b"dest", b"a", b"b"])).is_err()
+        );
+        assert!(BitOp::try_from(RespArray::command(&[b"bitop", b"nand", b"dest", b"a"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bit_commands_on_hash() -> Result<()> {
+        let backend = Backend::default();
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        let wrongtype: RespFrame =
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into();
+        assert_eq!(
+            run::<SetBit>(&backend, &[b"setbit", b"h", b"1", b"1"])?,
+            wrongtype
+        );
+        assert_eq!(
+            run::<GetBit>(&backend, &[b"getbit", b"h", b"1"])?,
+            wrongtype
+        );
+        assert_eq!(run::<BitCount>(&backend, &[b"bitcount", b"h"])?, wrongtype);
+        assert_eq!(
+            run::<BitOp>(&backend, &[b"bitop", b"and", b"dest", b"h"])?,
+            wrongtype
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_bit_arguments() {
+        assert!(SetBit::try_from(RespArray::command(&[b"setbit", b"k", b"-1", b"1"])).is_err());
+        assert!(
+            SetBit::try_from(RespArray::command(&[b"setbit", b"k", b"4294967296", b"1"])).is_err()
+        );
+        assert!(SetBit::try_from(RespArray::command(&[b"setbit", b"k", b"0", b"2"])).is_err());
+        assert!(BitCount::try_from(RespArray::command(&[b"bitcount", b"k", b"0"])).is_err());
+    }
+}
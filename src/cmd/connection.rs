@@ -0,0 +1,375 @@
+use crate::{
+    backend::Backend, BulkString, RespArray, RespFrame, RespMap, SimpleError, SimpleString,
+};
+
+use super::{
+    extract_args, validate_command_range, Command, CommandEntry, CommandError, CommandExecutor,
+    RESP_OK,
+};
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("ping", false, |v| v.try_into().map(Command::Ping)),
+    ("hello", false, |v| v.try_into().map(Command::Hello)),
+    ("client", false, |v| v.try_into().map(Command::Client)),
+];
+
+/// State of one client connection, read and changed by the connection commands.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionContext {
+    pub(crate) id: u64,
+    // RESP version negotiated through HELLO, connections start with RESP2
+    pub(crate) protocol: u8,
+    // set by CLIENT SETNAME, an empty name clears it
+    pub(crate) name: Option<String>,
+}
+
+/// Handshake reporting the server details, optionally switching the RESP version.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Hello {
+    protocol: Option<u8>,
+}
+
+/// Liveness check, answered without touching the keyspace.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Ping {
+    message: Option<BulkString>,
+}
+
+/// Introspection and settings of the calling connection.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Client {
+    Id,
+    GetName,
+    SetName(String),
+    List,
+    Kill(ClientFilter),
+}
+
+/// Selects the connections closed by `CLIENT KILL`.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum ClientFilter {
+    Id(u64),
+    Addr(String),
+}
+
+impl ConnectionContext {
+    pub fn new(id: u64) -> Self {
+        Self {
+            id,
+            protocol: 2,
+            name: None,
+        }
+    }
+}
+
+impl Default for ConnectionContext {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Hello {
+    pub(crate) fn execute_in(
+        self,
+        backend: &Backend,
+        context: &mut ConnectionContext,
+    ) -> RespFrame {
+        match self.protocol {
+            Some(protocol @ (2 | 3)) => context.protocol = protocol,
+            Some(_) => {
+                return RespFrame::SimpleError(SimpleError::new(
+                    "NOPROTO unsupported protocol version",
+                ))
+            }
+            None => {}
+        }
+        let role = if backend.is_replica() {
+            "replica"
+        } else {
+            "master"
+        };
+
+        let mut map = RespMap::new();
+        map.insert("server".to_string(), BulkString::from("redis").into());
+        map.insert(
+            "version".to_string(),
+            BulkString::from(env!("CARGO_PKG_VERSION")).into(),
+        );
+        map.insert(
+            "proto".to_string(),
+            RespFrame::Integer(context.protocol as i64),
+        );
+        map.insert("id".to_string(), RespFrame::Integer(context.id as i64));
+        map.insert("mode".to_string(), BulkString::from("standalone").into());
+        map.insert("role".to_string(), BulkString::from(role).into());
+        map.insert("modules".to_string(), RespArray::new(vec![]).into());
+        map.into()
+    }
+}
+
+impl Client {
+    pub(crate) fn execute_in(
+        self,
+        backend: &Backend,
+        context: &mut ConnectionContext,
+    ) -> RespFrame {
+        match self {
+            Client::Id => RespFrame::Integer(context.id as i64),
+            Client::GetName => match context.name {
+                Some(ref name) => BulkString::from(name.as_str()).into(),
+                None => BulkString::from("").into(),
+            },
+            Client::SetName(name) => {
+                if name.bytes().any(|b| !b.is_ascii_graphic()) {
+                    return RespFrame::SimpleError(SimpleError::new(
+                        "ERR Client names cannot contain spaces, newlines or special characters.",
+                    ));
+                }
+                context.name = (!name.is_empty()).then_some(name);
+                backend.set_client_name(context.id, context.name.clone());
+                RESP_OK.clone()
+            }
+            Client::List => {
+                let list = backend
+                    .clients()
+                    .into_iter()
+                    .map(|client| {
+                        format!(
+                            "id={} addr={} name={} db=0\n",
+                            client.id,
+                            client.addr,
+                            client.name.unwrap_or_default()
+                        )
+                    })
+                    .collect::<String>();
+                BulkString::from(list).into()
+            }
+            // like redis the calling connection is skipped
+            Client::Kill(filter) => {
+                let killed = backend.kill_clients(|client| {
+                    client.id != context.id
+                        && match filter {
+                            ClientFilter::Id(id) => client.id == id,
+                            ClientFilter::Addr(ref addr) => client.addr == *addr,
+                        }
+                });
+                RespFrame::Integer(killed as i64)
+            }
+        }
+    }
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.message {
+            Some(message) => message.into(),
+            None => SimpleString::new("PONG".to_string()).into(),
+        }
+    }
+}
+
+// outside of a connection, e.g. while applying a replication stream
+impl CommandExecutor for Hello {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_in(backend, &mut ConnectionContext::default())
+    }
+
+    async fn execute_async(self, backend: &Backend, context: &mut ConnectionContext) -> RespFrame {
+        self.execute_in(backend, context)
+    }
+}
+
+impl CommandExecutor for Client {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.execute_in(backend, &mut ConnectionContext::default())
+    }
+
+    async fn execute_async(self, backend: &Backend, context: &mut ConnectionContext) -> RespFrame {
+        self.execute_in(backend, context)
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["ping"], 0..=1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            None => Ok(Ping { message: None }),
+            Some(RespFrame::BulkString(message)) => Ok(Ping {
+                message: Some(message),
+            }),
+            Some(_) => Err(CommandError::InvalidArgument("Invalid message".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["hello"], 0..=1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            None => Ok(Hello { protocol: None }),
+            Some(protocol) => {
+                let protocol = i64::try_from(&protocol).map_err(|_| {
+                    CommandError::InvalidArgument(
+                        "Protocol version is not an integer or out of range".to_string(),
+                    )
+                })?;
+                Ok(Hello {
+                    protocol: Some(u8::try_from(protocol).unwrap_or(u8::MAX)),
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Client {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["client"], 1..=3)?;
+
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(String::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some((subcommand, args)) = args.split_first() else {
+            return Err(CommandError::WrongArity("client".to_string()));
+        };
+        match (subcommand.to_ascii_lowercase().as_str(), args) {
+            ("id", []) => Ok(Client::Id),
+            ("getname", []) => Ok(Client::GetName),
+            ("list", []) => Ok(Client::List),
+            ("setname", [name]) => Ok(Client::SetName(name.clone())),
+            ("kill", [filter, value]) => match filter.to_ascii_lowercase().as_str() {
+                "id" => Ok(Client::Kill(ClientFilter::Id(value.parse()?))),
+                "addr" => Ok(Client::Kill(ClientFilter::Addr(value.clone()))),
+                _ => Err(CommandError::InvalidArgument(format!(
+                    "unknown filter '{}'",
+                    filter
+                ))),
+            },
+            ("id" | "getname" | "setname" | "list" | "kill", _) => {
+                Err(CommandError::WrongArity(format!("client|{}", subcommand)))
+            }
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                subcommand
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{Client, ConnectionContext, Hello, Ping};
+    use crate::{
+        backend::Backend,
+        cmd::{run, RESP_OK},
+        BulkString, RespArray, RespFrame, SimpleError, SimpleString,
+    };
+
+    fn hello(context: &mut ConnectionContext, parts: &[&[u8]]) -> Result<RespFrame> {
+        let cmd = Hello::try_from(RespArray::command(parts))?;
+        Ok(cmd.execute_in(&Backend::default(), context))
+    }
+
+    fn proto(reply: &RespFrame) -> Option<&RespFrame> {
+        match reply {
+            RespFrame::Map(map) => map.get("proto"),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_hello_switches_protocol() -> Result<()> {
+        let mut context = ConnectionContext::new(7);
+
+        let reply = hello(&mut context, &[b"hello"])?;
+        assert_eq!(proto(&reply), Some(&RespFrame::Integer(2)));
+        let RespFrame::Map(ref map) = reply else {
+            panic!("hello must reply a map");
+        };
+        assert_eq!(map.get("id"), Some(&RespFrame::Integer(7)));
+
+        let reply = hello(&mut context, &[b"hello", b"3"])?;
+        assert_eq!(proto(&reply), Some(&RespFrame::Integer(3)));
+        assert_eq!(context.protocol, 3);
+
+        let reply = hello(&mut context, &[b"hello", b"2"])?;
+        assert_eq!(proto(&reply), Some(&RespFrame::Integer(2)));
+        assert_eq!(context.protocol, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_unsupported_protocol() -> Result<()> {
+        let mut context = ConnectionContext::new(1);
+        assert_eq!(
+            hello(&mut context, &[b"hello", b"4"])?,
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+        assert_eq!(context.protocol, 2);
+        assert!(Hello::try_from(RespArray::command(&[b"hello", b"three"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ping() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(
+            run::<Ping>(&backend, &[b"ping"])?,
+            SimpleString::new("PONG".to_string()).into()
+        );
+        assert_eq!(
+            run::<Ping>(&backend, &[b"PING", b"hello"])?,
+            BulkString::from("hello").into()
+        );
+        assert!(Ping::try_from(RespArray::command(&[b"ping", b"a", b"b"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_name_round_trip() -> Result<()> {
+        let backend = Backend::default();
+        let mut context = ConnectionContext::new(42);
+        let mut client = |parts: &[&[u8]]| -> Result<RespFrame> {
+            let cmd = Client::try_from(RespArray::command(parts))?;
+            Ok(cmd.execute_in(&backend, &mut context))
+        };
+
+        assert_eq!(client(&[b"client", b"ID"])?, RespFrame::Integer(42));
+        assert_eq!(
+            client(&[b"client", b"getname"])?,
+            BulkString::from("").into()
+        );
+        assert_eq!(
+            client(&[b"client", b"setname", b"worker-1"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            client(&[b"client", b"getname"])?,
+            BulkString::from("worker-1").into()
+        );
+        assert!(matches!(
+            client(&[b"client", b"setname", b"has space"])?,
+            RespFrame::SimpleError(_)
+        ));
+        assert_eq!(client(&[b"client", b"setname", b""])?, RESP_OK.clone());
+        assert_eq!(context.name, None);
+
+        assert!(Client::try_from(RespArray::command(&[b"client", b"setname"])).is_err());
+        assert!(Client::try_from(RespArray::command(&[b"client", b"nope"])).is_err());
+        assert!(Client::try_from(RespArray::command(&[b"client", b"kill", b"id", b"x"])).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{backend::Backend, RespArray, RespFrame};
+
+use super::{extract_args, validate_command, Command, CommandEntry, CommandError, CommandExecutor};
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("expire", true, |v| v.try_into().map(Command::Expire)),
+    ("pexpire", true, |v| v.try_into().map(Command::PExpire)),
+    ("expireat", true, |v| v.try_into().map(Command::ExpireAt)),
+    ("pexpireat", true, |v| v.try_into().map(Command::PExpireAt)),
+    ("ttl", false, |v| v.try_into().map(Command::Ttl)),
+    ("pttl", false, |v| v.try_into().map(Command::PTtl)),
+    ("expiretime", false, |v| {
+        v.try_into().map(Command::ExpireTime)
+    }),
+    ("pexpiretime", false, |v| {
+        v.try_into().map(Command::PExpireTime)
+    }),
+];
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PExpire {
+    key: String,
+    milliseconds: i64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct ExpireAt {
+    key: String,
+    timestamp: i64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PExpireAt {
+    key: String,
+    timestamp: i64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Ttl {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PTtl {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct ExpireTime {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PExpireTime {
+    key: String,
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_in(backend, &self.key, self.seconds.saturating_mul(1000))
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_in(backend, &self.key, self.milliseconds)
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_at(backend, &self.key, self.timestamp.saturating_mul(1000))
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire_at(backend, &self.key, self.timestamp)
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // round to the nearest second like redis does
+        remaining(backend, &self.key, |ttl| {
+            ((ttl.as_millis() + 500) / 1000) as i64
+        })
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        remaining(backend, &self.key, |ttl| ttl.as_millis() as i64)
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        absolute(backend, &self.key, |unix_milliseconds| {
+            unix_milliseconds / 1000
+        })
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        absolute(backend, &self.key, |unix_milliseconds| unix_milliseconds)
+    }
+}
+
+// a non-positive timeout deletes the key right away
+fn expire_in(backend: &Backend, key: &str, milliseconds: i64) -> RespFrame {
+    if !backend.exists(key) {
+        return RespFrame::Integer(0);
+    }
+    if milliseconds <= 0 {
+        backend.del(key);
+        return RespFrame::Integer(1);
+    }
+    let deadline = Instant::now() + Duration::from_millis(milliseconds as u64);
+    RespFrame::Integer(backend.expire(key, deadline) as i64)
+}
+
+// deadlines are monotonic, the backend maps the wall-clock timestamp onto one so EXPIRETIME reads it back unchanged
+fn expire_at(backend: &Backend, key: &str, unix_milliseconds: i64) -> RespFrame {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default();
+    if unix_milliseconds <= now {
+        return expire_in(backend, key, 0);
+    }
+    let deadline = backend.deadline_at(unix_milliseconds);
+    RespFrame::Integer(backend.expire(key, deadline) as i64)
+}
+
+// -2 for missing keys, -1 for keys without an expiry
+fn remaining(backend: &Backend, key: &str, unit: impl Fn(Duration) -> i64) -> RespFrame {
+    if !backend.exists(key) {
+        return RespFrame::Integer(-2);
+    }
+    match backend.ttl(key) {
+        Some(ttl) => RespFrame::Integer(unit(ttl)),
+        None => RespFrame::Integer(-1),
+    }
+}
+
+// -2 for missing keys, -1 for keys without an expiry
+fn absolute(backend: &Backend, key: &str, unit: impl Fn(i64) -> i64) -> RespFrame {
+    if !backend.exists(key) {
+        return RespFrame::Integer(-2);
+    }
+    match backend.expire_time(key) {
+        Some(unix_milliseconds) => RespFrame::Integer(unit(unix_milliseconds)),
+        None => RespFrame::Integer(-1),
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expire"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(seconds))) => {
+                Ok(Expire {
+                    key: String::from_utf8(key.0.into())?,
+                    seconds: String::from_utf8(seconds.0.into())?.parse()?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key or Seconds".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpire"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(milliseconds))) => {
+                Ok(PExpire {
+                    key: String::from_utf8(key.0.into())?,
+                    milliseconds: String::from_utf8(milliseconds.0.into())?.parse()?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key or Milliseconds".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expireat"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(timestamp))) => {
+                Ok(ExpireAt {
+                    key: String::from_utf8(key.0.into())?,
+                    timestamp: String::from_utf8(timestamp.0.into())?.parse()?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key or Timestamp".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpireat"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(timestamp))) => {
+                Ok(PExpireAt {
+                    key: String::from_utf8(key.0.into())?,
+                    timestamp: String::from_utf8(timestamp.0.into())?.parse()?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key or Timestamp".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ttl"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Ttl {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pttl"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(PTtl {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["expiretime"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(ExpireTime {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["pexpiretime"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(PExpireTime {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{
+        backend::Backend,
+        cmd::{Command, CommandExecutor},
+        BulkString, RespArray, RespFrame,
+    };
+
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{Expire, ExpireAt, PExpire, PExpireAt, PTtl, Ttl};
+
+    fn set_hello(backend: &Backend) {
+        backend.set(
+            "hello".to_string(),
+            RespFrame::BulkString(BulkString::new(b"world")),
+        );
+    }
+
+    #[test]
+    fn test_pexpire_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"pexpire", b"hello", b"1500"]);
+        let cmd = PExpire::try_from(resp_arr)?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.milliseconds, 1500);
+
+        let resp_arr = RespArray::command(&[b"pttl", b"hello"]);
+        let cmd: Command = RespFrame::Array(resp_arr).try_into()?;
+        assert!(matches!(cmd, Command::PTtl(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_then_pttl() -> Result<()> {
+        let backend = Backend::default();
+        set_hello(&backend);
+
+        let cmd = Expire {
+            key: "hello".to_string(),
+            seconds: 10,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = PTtl {
+            key: "hello".to_string(),
+        };
+        match cmd.execute(&backend) {
+            RespFrame::Integer(ms) => assert!(ms > 9_000 && ms <= 10_000),
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pexpire_then_ttl() -> Result<()> {
+        let backend = Backend::default();
+        set_hello(&backend);
+
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 5_000,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ttl_missing_and_persistent_key() -> Result<()> {
+        let backend = Backend::default();
+        let cmd = PTtl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+
+        set_hello(&backend);
+        let cmd = PTtl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        let cmd = PExpire {
+            key: "missing".to_string(),
+            milliseconds: 100,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pexpire_elapsed() -> Result<()> {
+        let backend = Backend::default();
+        set_hello(&backend);
+
+        let cmd = PExpire {
+            key: "hello".to_string(),
+            milliseconds: 0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("hello"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expireat_future_sets_ttl() -> Result<()> {
+        let backend = Backend::default();
+        set_hello(&backend);
+
+        let resp_arr = RespArray::command(&[b"expireat", b"hello", b"1700000000"]);
+        let cmd: Command = RespFrame::Array(resp_arr).try_into()?;
+        assert!(matches!(cmd, Command::ExpireAt(_)));
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let cmd = ExpireAt {
+            key: "hello".to_string(),
+            timestamp: now + 100,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = Ttl {
+            key: "hello".to_string(),
+        };
+        match cmd.execute(&backend) {
+            RespFrame::Integer(secs) => assert!(secs > 98 && secs <= 100),
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pexpireat_past_deletes_key() -> Result<()> {
+        let backend = Backend::default();
+        set_hello(&backend);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+        let cmd = PExpireAt {
+            key: "hello".to_string(),
+            timestamp: now - 1_000,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("hello"), None);
+
+        let cmd = PExpireAt {
+            key: "hello".to_string(),
+            timestamp: now + 1_000,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiretime_reads_back_expireat() -> Result<()> {
+        let backend = Backend::default();
+        let run = |parts: &[&[u8]]| -> Result<RespFrame> {
+            let cmd: Command = RespFrame::Array(RespArray::command(parts)).try_into()?;
+            Ok(cmd.execute(&backend))
+        };
+        assert_eq!(run(&[b"expiretime", b"hello"])?, RespFrame::Integer(-2));
+        set_hello(&backend);
+        assert_eq!(run(&[b"pexpiretime", b"hello"])?, RespFrame::Integer(-1));
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + 1000;
+        let timestamp = timestamp.to_string();
+        assert_eq!(
+            run(&[b"expireat", b"hello", timestamp.as_bytes()])?,
+            RespFrame::Integer(1)
+        );
+        let timestamp: i64 = timestamp.parse()?;
+        assert_eq!(
+            run(&[b"expiretime", b"hello"])?,
+            RespFrame::Integer(timestamp)
+        );
+        assert_eq!(
+            run(&[b"pexpiretime", b"hello"])?,
+            RespFrame::Integer(timestamp * 1000)
+        );
+        Ok(())
+    }
+}
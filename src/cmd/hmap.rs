@@ -1,6 +1,28 @@
-use crate::{backend::Backend, RespArray, RespFrame, RespMap, RespNull};
+use crate::{
+    backend::Backend, glob::glob_match, BulkString, NullBulkString, RespArray, RespFrame, RespMap,
+    SimpleError,
+};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use super::{
+    ensure_hash, extract_args,
+    keyspace::{parse_scan_args, scan_page, scan_reply},
+    validate_command, validate_command_range, Command, CommandEntry, CommandError, CommandExecutor,
+    RESP_OK,
+};
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("hget", false, |v| v.try_into().map(Command::HGet)),
+    ("hset", true, |v| v.try_into().map(Command::HSet)),
+    ("hsetnx", true, |v| v.try_into().map(Command::HSetNx)),
+    ("hgetall", false, |v| v.try_into().map(Command::HGetAll)),
+    ("hexists", false, |v| v.try_into().map(Command::HExists)),
+    ("hincrby", true, |v| v.try_into().map(Command::HIncrBy)),
+    ("hincrbyfloat", true, |v| {
+        v.try_into().map(Command::HIncrByFloat)
+    }),
+    ("hscan", false, |v| v.try_into().map(Command::HScan)),
+];
 
 #[derive(Debug, PartialEq, PartialOrd)]
 pub struct HGet {
@@ -17,8 +39,8 @@ impl TryFrom<RespArray> for HGet {
         let mut args = extract_args(arr, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(map)), Some(RespFrame::BulkString(key))) => Ok(Self {
-                key: String::from_utf8(map.0)?,
-                field: String::from_utf8(key.0)?,
+                key: String::from_utf8(map.0.into())?,
+                field: String::from_utf8(key.0.into())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
         }
@@ -29,7 +51,7 @@ impl CommandExecutor for HGet {
     fn execute(self, backend: &Backend) -> RespFrame {
         match backend.hget(&self.key, &self.field) {
             Some(value) => value,
-            None => RespFrame::Null(RespNull),
+            None => NullBulkString.into(),
         }
     }
 }
@@ -51,8 +73,8 @@ impl TryFrom<RespArray> for HSet {
         match (args.next(), args.next(), args.next()) {
             (Some(RespFrame::BulkString(map)), Some(RespFrame::BulkString(key)), Some(value)) => {
                 Ok(Self {
-                    key: String::from_utf8(map.0)?,
-                    field: String::from_utf8(key.0)?,
+                    key: String::from_utf8(map.0.into())?,
+                    field: String::from_utf8(key.0.into())?,
                     value,
                 })
             }
@@ -63,11 +85,50 @@ impl TryFrom<RespArray> for HSet {
 
 impl CommandExecutor for HSet {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_hash(backend, &self.key) {
+            return e;
+        }
         backend.hset(self.key, self.field, self.value);
         RESP_OK.clone()
     }
 }
 
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct HSetNx {
+    key: String,
+    field: String,
+    value: RespFrame,
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hsetnx"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(RespFrame::BulkString(map)), Some(RespFrame::BulkString(key)), Some(value)) => {
+                Ok(Self {
+                    key: String::from_utf8(map.0.into())?,
+                    field: String::from_utf8(key.0.into())?,
+                    value,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl CommandExecutor for HSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_hash(backend, &self.key) {
+            return e;
+        }
+        RespFrame::Integer(backend.hsetnx(self.key, self.field, self.value) as i64)
+    }
+}
+
 #[derive(Debug, PartialEq, PartialOrd)]
 pub struct HGetAll {
     key: String,
@@ -82,7 +143,7 @@ impl TryFrom<RespArray> for HGetAll {
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Self {
-                key: String::from_utf8(key.0)?,
+                key: String::from_utf8(key.0.into())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
         }
@@ -91,36 +152,229 @@ impl TryFrom<RespArray> for HGetAll {
 
 impl CommandExecutor for HGetAll {
     fn execute(self, backend: &Backend) -> RespFrame {
+        // the dashmap iterates in shard order, RespMap sorts the fields so the reply is stable
+        let mut ret = RespMap::new();
+        // a missing key reads as an empty hash
         if let Some(map) = backend.hgetall(self.key.as_str()) {
-            let mut ret = RespMap::new();
             map.iter().for_each(|e| {
                 ret.insert(e.key().clone(), e.value().clone());
             });
-            ret.into()
-        } else {
-            RespFrame::Null(RespNull)
         }
+        ret.into()
+    }
+}
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct HExists {
+    key: String,
+    field: String,
+}
+
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["hexists"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(map)), Some(RespFrame::BulkString(key))) => Ok(Self {
+                key: String::from_utf8(map.0.into())?,
+                field: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.hexists(&self.key, &self.field) as i64)
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct HIncrBy {
+    key: String,
+    field: String,
+    delta: i64,
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(field)),
+                Some(RespFrame::BulkString(delta)),
+            ) => Ok(Self {
+                key: String::from_utf8(key.0.into())?,
+                field: String::from_utf8(field.0.into())?,
+                delta: String::from_utf8(delta.0.into())?.parse()?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_hash(backend, &self.key) {
+            return e;
+        }
+        let mut value = 0;
+        let ret = backend.hupdate(self.key, self.field, |current| {
+            let current = match current {
+                Some(RespFrame::BulkString(v)) => String::from_utf8_lossy(v.as_ref())
+                    .parse::<i64>()
+                    .map_err(|_| "ERR hash value is not an integer")?,
+                Some(RespFrame::Integer(v)) => *v,
+                Some(_) => return Err("ERR hash value is not an integer"),
+                None => 0,
+            };
+            value = current
+                .checked_add(self.delta)
+                .ok_or("ERR increment or decrement would overflow")?;
+            Ok(BulkString::new(value.to_string()).into())
+        });
+        match ret {
+            Ok(_) => RespFrame::Integer(value),
+            Err(e) => RespFrame::SimpleError(SimpleError::new(e)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct HIncrByFloat {
+    key: String,
+    field: String,
+    delta: f64,
+}
+
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrbyfloat"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(field)),
+                Some(RespFrame::BulkString(delta)),
+            ) => Ok(Self {
+                key: String::from_utf8(key.0.into())?,
+                field: String::from_utf8(field.0.into())?,
+                delta: String::from_utf8(delta.0.into())?.parse()?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_hash(backend, &self.key) {
+            return e;
+        }
+        let ret = backend.hupdate(self.key, self.field, |current| {
+            let current = match current {
+                Some(RespFrame::BulkString(v)) => String::from_utf8_lossy(v.as_ref())
+                    .parse::<f64>()
+                    .map_err(|_| "ERR hash value is not a float")?,
+                Some(RespFrame::Integer(v)) => *v as f64,
+                Some(RespFrame::Double(v)) => *v,
+                Some(_) => return Err("ERR hash value is not a float"),
+                None => 0.0,
+            };
+            let value = current + self.delta;
+            if !value.is_finite() {
+                return Err("ERR increment would produce NaN or Infinity");
+            }
+            Ok(BulkString::new(value.to_string()).into())
+        });
+        match ret {
+            Ok(v) => v,
+            Err(e) => RespFrame::SimpleError(SimpleError::new(e)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct HScan {
+    key: String,
+    cursor: usize,
+    pattern: Option<String>,
+    count: usize,
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // key and cursor plus up to two MATCH / COUNT option pairs
+        validate_command_range(&value, &["hscan"], 2..=6)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArgument("Invalid key".into())),
+        };
+        let (cursor, pattern, count) = parse_scan_args(args)?;
+        Ok(Self {
+            key,
+            cursor,
+            pattern,
+            count,
+        })
+    }
+}
+
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // the cursor is a position in the sorted field snapshot
+        let mut fields = match backend.hgetall(self.key.as_str()) {
+            Some(map) => map.into_iter().collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let (page, next_cursor) = scan_page(&fields, self.cursor, self.count);
+
+        let page = page
+            .iter()
+            .filter(|(field, _)| match self.pattern {
+                Some(ref pattern) => glob_match(pattern.as_bytes(), field.as_bytes()),
+                None => true,
+            })
+            .flat_map(|(field, value)| [BulkString::new(field.as_bytes()).into(), value.clone()])
+            .collect::<Vec<RespFrame>>();
+
+        scan_reply(next_cursor, page)
     }
 }
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use anyhow::Result;
 
     use crate::{
         backend::Backend,
-        cmd::{hmap::HGetAll, CommandExecutor, RESP_OK},
-        BulkString, RespArray, RespFrame, RespMap,
+        cmd::{expire::PExpire, hmap::HGetAll, run, CommandExecutor, RESP_OK},
+        BulkString, NullBulkString, RespArray, RespEncode, RespFrame, RespMap, SimpleError,
     };
 
-    use super::{HGet, HSet};
+    use super::{HExists, HGet, HIncrBy, HIncrByFloat, HScan, HSet, HSetNx};
 
     #[test]
     fn test_hget_from_resp_array() -> Result<()> {
-        let resp_arr = RespArray::new(vec![
-            RespFrame::BulkString(BulkString(b"hget".into())),
-            RespFrame::BulkString(BulkString(b"map1".into())),
-            RespFrame::BulkString(BulkString(b"key".into())),
-        ]);
+        let resp_arr = RespArray::command(&[b"hget", b"map1", b"key"]);
         let cmd = HGet::try_from(resp_arr)?;
         assert_eq!(cmd.key, "map1");
         assert_eq!(cmd.field, "key");
@@ -129,12 +383,7 @@ mod tests {
 
     #[test]
     fn test_hset_from_resp_array() -> Result<()> {
-        let resp_arr = RespArray::new(vec![
-            RespFrame::BulkString(BulkString(b"hset".into())),
-            RespFrame::BulkString(BulkString(b"map1".into())),
-            RespFrame::BulkString(BulkString(b"key".into())),
-            RespFrame::BulkString(BulkString(b"value".into())),
-        ]);
+        let resp_arr = RespArray::command(&[b"hset", b"map1", b"key", b"value"]);
         let cmd = HSet::try_from(resp_arr)?;
         assert_eq!(cmd.key, "map1");
         assert_eq!(cmd.field, "key");
@@ -142,20 +391,116 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hsetnx_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"hsetnx", b"map1", b"key", b"value"]);
+        let cmd = HSetNx::try_from(resp_arr)?;
+        assert_eq!(cmd.key, "map1");
+        assert_eq!(cmd.field, "key");
+        assert_eq!(cmd.value, RespFrame::BulkString(BulkString::new(b"value")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hsetnx_command() -> Result<()> {
+        let backend = Backend::default();
+
+        let resp = HSetNx {
+            key: "map1".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(BulkString::new(b"world")),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RespFrame::Integer(1));
+
+        let resp = HSetNx {
+            key: "map1".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(BulkString::new(b"again")),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RespFrame::Integer(0));
+        assert_eq!(
+            backend.hget("map1", "hello"),
+            Some(RespFrame::BulkString(BulkString::new(b"world")))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_hgetall_from_resp_array() -> Result<()> {
-        let resp_arr = RespArray::new(vec![
-            RespFrame::BulkString(BulkString(b"hgetall".into())),
-            RespFrame::BulkString(BulkString(b"map1".into())),
-        ]);
+        let resp_arr = RespArray::command(&[b"hgetall", b"map1"]);
         let hgetall = HGetAll::try_from(resp_arr)?;
         assert_eq!(hgetall.key, "map1");
         Ok(())
     }
 
+    #[test]
+    fn test_hgetall_missing_key() {
+        let cmd = HGetAll {
+            key: "missing".to_string(),
+        };
+        let reply = cmd.execute(&Backend::default());
+        assert_eq!(reply, RespMap::new().into());
+        assert_eq!(reply.clone().into_resp2().encode(), b"*0\r\n");
+        assert_eq!(reply.into_resp3().encode(), b"%0\r\n");
+    }
+
+    #[test]
+    fn test_hgetall_order_is_stable() {
+        let fields = (0..64).map(|i| format!("field{}", i)).collect::<Vec<_>>();
+        let hgetall = |fields: &mut dyn Iterator<Item = &String>| {
+            let backend = Backend::default();
+            for field in fields {
+                backend.hset(
+                    "h".to_string(),
+                    field.clone(),
+                    BulkString::new(field.as_bytes()).into(),
+                );
+            }
+            let cmd = HGetAll {
+                key: "h".to_string(),
+            };
+            cmd.execute(&backend).encode()
+        };
+
+        let forward = hgetall(&mut fields.iter());
+        assert_eq!(hgetall(&mut fields.iter().rev()), forward);
+        // fields come out sorted, field10 before field2
+        let reply = String::from_utf8_lossy(&forward).into_owned();
+        let position = |field: &str| {
+            reply
+                .find(&format!("${}\r\n{}\r\n", field.len(), field))
+                .unwrap()
+        };
+        assert!(position("field10") < position("field2"));
+        assert!(position("field0") < position("field1"));
+    }
+
+    #[test]
+    fn test_hset_on_expired_hash() -> Result<()> {
+        let backend = Backend::default();
+        run::<HSet>(&backend, &[b"hset", b"h", b"old", b"v"])?;
+        run::<PExpire>(&backend, &[b"pexpire", b"h", b"1"])?;
+        std::thread::sleep(Duration::from_millis(5));
+
+        // the hash expired but was never read, so it is still stored
+        run::<HSet>(&backend, &[b"hset", b"h", b"new", b"v"])?;
+        assert_eq!(
+            run::<HGet>(&backend, &[b"hget", b"h", b"new"])?,
+            BulkString::new(b"v").into()
+        );
+        assert_eq!(
+            run::<HGet>(&backend, &[b"hget", b"h", b"old"])?,
+            NullBulkString.into()
+        );
+        assert_eq!(backend.ttl("h"), None);
+        Ok(())
+    }
+
     #[test]
     fn test_hset_get_command() -> Result<()> {
-        let backend = Backend::new();
+        let backend = Backend::default();
 
         let sets = vec![
             HSet {
@@ -196,4 +541,240 @@ mod tests {
         assert_eq!(resp, rval.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hexists_command() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"hexists", b"map1", b"hello"]);
+        let cmd = HExists::try_from(resp_arr)?;
+        assert_eq!(cmd.key, "map1");
+        assert_eq!(cmd.field, "hello");
+
+        let backend = Backend::default();
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        backend.hset(
+            "map1".to_string(),
+            "hello".to_string(),
+            RespFrame::BulkString(BulkString::new(b"world")),
+        );
+        let hexists = |field: &str| {
+            HExists {
+                key: "map1".to_string(),
+                field: field.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hexists("hello"), RespFrame::Integer(1));
+        assert_eq!(hexists("missing"), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"hincrby", b"map1", b"field", b"-5"]);
+        let cmd = HIncrBy::try_from(resp_arr)?;
+        assert_eq!(cmd.key, "map1");
+        assert_eq!(cmd.field, "field");
+        assert_eq!(cmd.delta, -5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_command() -> Result<()> {
+        let backend = Backend::default();
+        let hincrby = |field: &str, delta| {
+            HIncrBy {
+                key: "map1".to_string(),
+                field: field.to_string(),
+                delta,
+            }
+            .execute(&backend)
+        };
+
+        // a missing field starts at zero
+        assert_eq!(hincrby("counter", 5), RespFrame::Integer(5));
+        assert_eq!(hincrby("counter", -8), RespFrame::Integer(-3));
+        assert_eq!(
+            backend.hget("map1", "counter"),
+            Some(BulkString::new(b"-3").into())
+        );
+
+        backend.hset(
+            "map1".to_string(),
+            "numeric".to_string(),
+            BulkString::new(b"10").into(),
+        );
+        assert_eq!(hincrby("numeric", 1), RespFrame::Integer(11));
+
+        backend.hset(
+            "map1".to_string(),
+            "text".to_string(),
+            BulkString::new(b"hello").into(),
+        );
+        assert_eq!(
+            hincrby("text", 1),
+            SimpleError::new("ERR hash value is not an integer").into()
+        );
+
+        hincrby("big", i64::MAX);
+        assert_eq!(
+            hincrby("big", 1),
+            SimpleError::new("ERR increment or decrement would overflow").into()
+        );
+        assert_eq!(
+            backend.hget("map1", "big"),
+            Some(BulkString::new(i64::MAX.to_string()).into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrbyfloat_command() -> Result<()> {
+        let backend = Backend::default();
+        let hincrbyfloat = |field: &str, delta| {
+            HIncrByFloat {
+                key: "map1".to_string(),
+                field: field.to_string(),
+                delta,
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(hincrbyfloat("f", 10.5), BulkString::new(b"10.5").into());
+        assert_eq!(hincrbyfloat("f", 0.1), BulkString::new(b"10.6").into());
+        assert_eq!(hincrbyfloat("f", -10.6), BulkString::new(b"0").into());
+
+        backend.hset(
+            "map1".to_string(),
+            "g".to_string(),
+            BulkString::new(b"5.0e3").into(),
+        );
+        assert_eq!(hincrbyfloat("g", 2.0e2), BulkString::new(b"5200").into());
+
+        backend.hset(
+            "map1".to_string(),
+            "text".to_string(),
+            BulkString::new(b"hello").into(),
+        );
+        assert_eq!(
+            hincrbyfloat("text", 1.0),
+            SimpleError::new("ERR hash value is not a float").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_error_keeps_keyspace_clean() -> Result<()> {
+        let backend = Backend::default();
+        let resp = HIncrByFloat {
+            key: "map1".to_string(),
+            field: "f".to_string(),
+            delta: f64::INFINITY,
+        }
+        .execute(&backend);
+        assert_eq!(
+            resp,
+            SimpleError::new("ERR increment would produce NaN or Infinity").into()
+        );
+        assert!(!backend.exists("map1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_writes_on_string_key() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("s".to_string(), BulkString::new(b"v").into());
+        let wrongtype =
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value");
+        for args in [
+            &[&b"hset"[..], b"s", b"f", b"1"][..],
+            &[b"hsetnx", b"s", b"f", b"1"],
+            &[b"hincrby", b"s", b"f", b"1"],
+            &[b"hincrbyfloat", b"s", b"f", b"1.5"],
+        ] {
+            let resp = match args[0] {
+                b"hset" => run::<HSet>(&backend, args)?,
+                b"hsetnx" => run::<HSetNx>(&backend, args)?,
+                b"hincrby" => run::<HIncrBy>(&backend, args)?,
+                _ => run::<HIncrByFloat>(&backend, args)?,
+            };
+            assert_eq!(resp, wrongtype.clone().into());
+        }
+        // the string is left as it was
+        assert_eq!(backend.get("s"), Some(BulkString::new(b"v").into()));
+        assert_eq!(backend.key_type("s"), Some("string"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"hscan", b"map", b"3", b"COUNT", b"5"]);
+        let cmd = HScan::try_from(resp_arr)?;
+        assert_eq!(cmd.key, "map");
+        assert_eq!(cmd.cursor, 3);
+        assert_eq!(cmd.pattern, None);
+        assert_eq!(cmd.count, 5);
+
+        let resp_arr = RespArray::command(&[b"hscan", b"map", b"0", b"COUNT", b"0"]);
+        assert!(HScan::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    fn hscan_all(backend: &Backend, key: &str, pattern: Option<&str>) -> Vec<(String, RespFrame)> {
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = HScan {
+                key: key.to_string(),
+                cursor,
+                pattern: pattern.map(|p| p.to_string()),
+                count: 4,
+            };
+            let RespFrame::Array(resp) = cmd.execute(backend) else {
+                panic!("expected array reply");
+            };
+            let mut items = resp.into_iter();
+            let Some(RespFrame::BulkString(next)) = items.next() else {
+                panic!("expected cursor");
+            };
+            let Some(RespFrame::Array(page)) = items.next() else {
+                panic!("expected page");
+            };
+            let mut page = page.into_iter();
+            while let (Some(RespFrame::BulkString(field)), Some(value)) = (page.next(), page.next())
+            {
+                seen.push((String::from_utf8_lossy(field.as_ref()).into_owned(), value));
+            }
+            cursor = String::from_utf8_lossy(next.as_ref()).parse().unwrap();
+            if cursor == 0 {
+                return seen;
+            }
+        }
+    }
+
+    #[test]
+    fn test_hscan_visits_every_field_once() {
+        let backend = Backend::default();
+        for i in 0..15 {
+            backend.hset(
+                "map".to_string(),
+                format!("field{}", i),
+                BulkString::new(format!("value{}", i)).into(),
+            );
+        }
+
+        let mut seen = hscan_all(&backend, "map", None);
+        assert_eq!(seen.len(), 15);
+        seen.sort_by(|a, b| a.0.cmp(&b.0));
+        seen.dedup_by(|a, b| a.0 == b.0);
+        assert_eq!(seen.len(), 15);
+        for (field, value) in seen {
+            let expected = field.replace("field", "value");
+            assert_eq!(value, BulkString::new(expected).into());
+        }
+
+        let seen = hscan_all(&backend, "map", Some("field1*"));
+        assert_eq!(seen.len(), 6);
+        assert!(hscan_all(&backend, "missing", None).is_empty());
+    }
 }
@@ -0,0 +1,1015 @@
+use std::time::{Duration, Instant};
+
+use bytes::BytesMut;
+
+use crate::{
+    backend::{Backend, BackendSnapshot},
+    glob::glob_match,
+    resp::frame_length,
+    BulkString, NullBulkString, RespArray, RespDecode, RespEncode, RespFrame, SimpleError,
+};
+
+use super::{
+    extract_args, validate_command, validate_command_range, Command, CommandEntry, CommandError,
+    CommandExecutor, RESP_OK,
+};
+
+const DEFAULT_SCAN_COUNT: usize = 10;
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("scan", false, |v| v.try_into().map(Command::Scan)),
+    ("randomkey", false, |v| v.try_into().map(Command::RandomKey)),
+    ("dbsize", false, |v| v.try_into().map(Command::DbSize)),
+    ("object", false, |v| v.try_into().map(Command::Object)),
+    ("copy", true, |v| v.try_into().map(Command::Copy)),
+    ("touch", false, |v| v.try_into().map(Command::Touch)),
+    ("unlink", true, |v| v.try_into().map(Command::Unlink)),
+    ("dump", false, |v| v.try_into().map(Command::Dump)),
+    ("restore", true, |v| v.try_into().map(Command::Restore)),
+];
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Scan {
+    cursor: usize,
+    pattern: Option<String>,
+    count: usize,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct RandomKey;
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct DbSize;
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Copy {
+    source: String,
+    destination: String,
+    replace: bool,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Touch {
+    keys: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Object {
+    Encoding(String),
+    IdleTime(String),
+    RefCount(String),
+    /// always 0, access frequency is only tracked by LFU eviction which is not implemented
+    Freq(String),
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Dump {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Restore {
+    key: String,
+    // milliseconds, 0 restores a persistent key
+    ttl: i64,
+    payload: Vec<u8>,
+    replace: bool,
+    // ABSTTL, the ttl is a unix time in milliseconds instead
+    absttl: bool,
+}
+
+// DUMP payloads are a RESP array of the value type and the value itself,
+// hashes are flattened into alternating field and value frames
+const DUMP_STRING: &[u8] = b"string";
+const DUMP_HASH: &[u8] = b"hash";
+
+pub(crate) enum DumpValue {
+    String(RespFrame),
+    Hash(Vec<(String, RespFrame)>),
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // the cursor is a position in the sorted key snapshot
+        let keys = backend.keys();
+        let (page, next_cursor) = scan_page(&keys, self.cursor, self.count);
+
+        let page = page
+            .iter()
+            .filter(|key| match self.pattern {
+                Some(ref pattern) => glob_match(pattern.as_bytes(), key.as_bytes()),
+                None => true,
+            })
+            .map(|key| BulkString::new(key.as_bytes()).into())
+            .collect::<Vec<RespFrame>>();
+
+        scan_reply(next_cursor, page)
+    }
+}
+
+// slices the page starting at cursor, the returned cursor is 0 once the snapshot is exhausted
+pub(super) fn scan_page<T>(items: &[T], cursor: usize, count: usize) -> (&[T], usize) {
+    let start = cursor.min(items.len());
+    let end = start.saturating_add(count).min(items.len());
+    let next_cursor = if end >= items.len() { 0 } else { end };
+    (&items[start..end], next_cursor)
+}
+
+pub(super) fn scan_reply(next_cursor: usize, page: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(vec![
+        BulkString::new(next_cursor.to_string()).into(),
+        RespArray::new(page).into(),
+    ])
+    .into()
+}
+
+impl CommandExecutor for RandomKey {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.random_key() {
+            Some(key) => BulkString::new(key).into(),
+            None => NullBulkString.into(),
+        }
+    }
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for Copy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.copy(&self.source, &self.destination, self.replace) as i64)
+    }
+}
+
+impl CommandExecutor for Touch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let touched = self.keys.iter().filter(|key| backend.touch(key)).count();
+        RespFrame::Integer(touched as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let removed = self.keys.iter().filter(|key| backend.unlink(key)).count();
+        RespFrame::Integer(removed as i64)
+    }
+}
+
+impl CommandExecutor for Object {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            Object::Encoding(key) => match backend.encoding(&key) {
+                Some(encoding) => BulkString::new(encoding).into(),
+                None => no_such_key(),
+            },
+            Object::IdleTime(key) => match backend.idle_time(&key) {
+                Some(idle) => RespFrame::Integer(idle.as_secs() as i64),
+                None => no_such_key(),
+            },
+            // values are never shared between keys
+            Object::RefCount(key) if backend.exists(&key) => RespFrame::Integer(1),
+            Object::Freq(key) if backend.exists(&key) => RespFrame::Integer(0),
+            Object::RefCount(_) | Object::Freq(_) => no_such_key(),
+        }
+    }
+}
+
+impl CommandExecutor for Dump {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let value = if let Some(value) = backend.get(&self.key) {
+            DumpValue::String(value)
+        } else if let Some(hmap) = backend.hgetall(&self.key) {
+            DumpValue::Hash(hmap.into_iter().collect())
+        } else {
+            return NullBulkString.into();
+        };
+        BulkString::new(dump_payload(value)).into()
+    }
+}
+
+pub(crate) fn dump_payload(value: DumpValue) -> Vec<u8> {
+    let payload = match value {
+        DumpValue::String(value) => {
+            RespArray::new(vec![BulkString::new(DUMP_STRING).into(), value])
+        }
+        DumpValue::Hash(fields) => {
+            let fields = fields
+                .into_iter()
+                .flat_map(|(field, value)| [BulkString::new(field).into(), value])
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(vec![
+                BulkString::new(DUMP_HASH).into(),
+                RespArray::new(fields).into(),
+            ])
+        }
+    };
+    payload.encode()
+}
+
+/// RESTORE commands recreating every key of the snapshot, with its deadline.
+///
+/// Deadlines are sent as unix time, so the time the commands take to be applied does not
+/// extend them.
+pub(crate) fn restore_commands(backend: &Backend, snapshot: BackendSnapshot) -> Vec<RespFrame> {
+    let BackendSnapshot {
+        strings,
+        hashes,
+        expires,
+    } = snapshot;
+    let restore = |key: String, value: DumpValue| {
+        // a ttl of 0 would make the key persistent
+        let ttl = match expires.get(&key) {
+            Some(deadline) => backend.unix_millis_of(*deadline).max(1),
+            None => 0,
+        };
+        RespArray::command(&[
+            b"restore",
+            key.as_bytes(),
+            ttl.to_string().as_bytes(),
+            &dump_payload(value),
+            b"replace",
+            b"absttl",
+        ])
+        .into()
+    };
+
+    let strings = strings
+        .into_iter()
+        .map(|(key, value)| restore(key, DumpValue::String(value)));
+    let hashes = hashes
+        .into_iter()
+        .map(|(key, fields)| restore(key, DumpValue::Hash(fields.into_iter().collect())));
+    strings.chain(hashes).collect()
+}
+
+/// Rebuild the keyspace described by RESTORE commands, the inverse of `restore_commands`.
+pub(crate) fn snapshot_from_restore_commands(
+    backend: &Backend,
+    commands: impl IntoIterator<Item = RespArray>,
+) -> Result<BackendSnapshot, CommandError> {
+    let now = Instant::now();
+    let mut snapshot = BackendSnapshot::default();
+    for command in commands {
+        let Restore {
+            key,
+            ttl,
+            payload,
+            absttl,
+            ..
+        } = command.try_into()?;
+        match parse_dump(&payload) {
+            Some(DumpValue::String(value)) => {
+                snapshot.strings.insert(key.clone(), value);
+            }
+            Some(DumpValue::Hash(fields)) => {
+                snapshot
+                    .hashes
+                    .insert(key.clone(), fields.into_iter().collect());
+            }
+            None => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "invalid payload for key {}",
+                    key
+                )))
+            }
+        }
+        if ttl > 0 && absttl {
+            snapshot.expires.insert(key, backend.deadline_at(ttl));
+        } else if ttl > 0 {
+            let deadline = now + Duration::from_millis(ttl as u64);
+            snapshot.expires.insert(key, deadline);
+        }
+    }
+    Ok(snapshot)
+}
+
+impl CommandExecutor for Restore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.ttl < 0 {
+            return RespFrame::SimpleError(SimpleError::new("ERR Invalid TTL value, must be >= 0"));
+        }
+        let value = match parse_dump(&self.payload) {
+            Some(value) => value,
+            None => {
+                return RespFrame::SimpleError(SimpleError::new(
+                    "ERR DUMP payload version or checksum are wrong",
+                ))
+            }
+        };
+        if !self.replace && backend.exists(&self.key) {
+            return RespFrame::SimpleError(SimpleError::new(
+                "BUSYKEY Target key name already exists.",
+            ));
+        }
+
+        // the value and its deadline replace the old key in one step
+        let deadline = match self.ttl {
+            0 => None,
+            ttl if self.absttl => Some(backend.deadline_at(ttl)),
+            ttl => Some(Instant::now() + Duration::from_millis(ttl as u64)),
+        };
+        match value {
+            DumpValue::String(value) => backend.set_with_deadline(self.key, value, deadline),
+            DumpValue::Hash(fields) => backend.set_hash_with_deadline(self.key, fields, deadline),
+        }
+        RESP_OK.clone()
+    }
+}
+
+fn parse_dump(payload: &[u8]) -> Option<DumpValue> {
+    // the payload comes from the client, only decode it once its declared lengths add up
+    if frame_length(payload).ok()? != Some(payload.len()) {
+        return None;
+    }
+    let mut buf = BytesMut::from(payload);
+    let frame = RespArray::decode(&mut buf).ok()?;
+    let mut parts = frame.into_iter();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(RespFrame::BulkString(kind)), Some(value), None) if kind.as_ref() == DUMP_STRING => {
+            Some(DumpValue::String(value))
+        }
+        (Some(RespFrame::BulkString(kind)), Some(RespFrame::Array(fields)), None)
+            if kind.as_ref() == DUMP_HASH && !fields.is_empty() && fields.len() % 2 == 0 =>
+        {
+            let mut fields = fields.into_iter();
+            let mut hash = vec![];
+            while let (Some(RespFrame::BulkString(field)), Some(value)) =
+                (fields.next(), fields.next())
+            {
+                hash.push((String::from_utf8(field.0.into()).ok()?, value));
+            }
+            Some(DumpValue::Hash(hash))
+        }
+        _ => None,
+    }
+}
+
+fn no_such_key() -> RespFrame {
+    RespFrame::SimpleError(SimpleError::new("ERR no such key"))
+}
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // cursor plus up to two MATCH / COUNT option pairs
+        validate_command_range(&value, &["scan"], 1..=5)?;
+
+        let (cursor, pattern, count) = parse_scan_args(extract_args(value, 1)?.into_iter())?;
+        Ok(Scan {
+            cursor,
+            pattern,
+            count,
+        })
+    }
+}
+
+// parses `cursor [MATCH pattern] [COUNT count]`, shared by the SCAN family
+pub(super) fn parse_scan_args(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<(usize, Option<String>, usize), CommandError> {
+    let cursor = match args.next() {
+        Some(RespFrame::BulkString(cursor)) => String::from_utf8(cursor.0.into())?
+            .parse()
+            .map_err(|_| CommandError::InvalidArgument("invalid cursor".to_string()))?,
+        _ => return Err(CommandError::InvalidArgument("Invalid cursor".to_string())),
+    };
+
+    let mut pattern = None;
+    let mut count = DEFAULT_SCAN_COUNT;
+    while let Some(option) = args.next() {
+        match (option, args.next()) {
+            (RespFrame::BulkString(option), Some(RespFrame::BulkString(arg))) => {
+                match option.as_ref().to_ascii_lowercase().as_slice() {
+                    b"match" => pattern = Some(String::from_utf8(arg.0.into())?),
+                    b"count" => {
+                        count = String::from_utf8(arg.0.into())?.parse()?;
+                        if count == 0 {
+                            return Err(CommandError::InvalidArgument("syntax error".to_string()));
+                        }
+                    }
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                }
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+    Ok((cursor, pattern, count))
+}
+
+impl TryFrom<RespArray> for RandomKey {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["randomkey"], 0)?;
+        Ok(RandomKey)
+    }
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dbsize"], 0)?;
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // source and destination plus an optional REPLACE flag
+        validate_command_range(&value, &["copy"], 2..=3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (source, destination) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(source)), Some(RespFrame::BulkString(destination))) => (
+                String::from_utf8(source.0.into())?,
+                String::from_utf8(destination.0.into())?,
+            ),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid source or destination".to_string(),
+                ))
+            }
+        };
+        let replace = match args.next() {
+            Some(RespFrame::BulkString(flag)) if flag.as_ref().eq_ignore_ascii_case(b"replace") => {
+                true
+            }
+            None => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(Copy {
+            source,
+            destination,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["touch"], 1..)?;
+
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(key) => Ok(String::from_utf8(key.0.into())?),
+                _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+            })
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(Touch { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["unlink"], 1..)?;
+
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(key) => Ok(String::from_utf8(key.0.into())?),
+                _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+            })
+            .collect::<Result<Vec<String>, CommandError>>()?;
+        Ok(Unlink { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["object"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(subcommand)), Some(RespFrame::BulkString(key))) => {
+                match subcommand.as_ref().to_ascii_lowercase().as_slice() {
+                    b"encoding" => Ok(Object::Encoding(String::from_utf8(key.0.into())?)),
+                    b"idletime" => Ok(Object::IdleTime(String::from_utf8(key.0.into())?)),
+                    b"refcount" => Ok(Object::RefCount(String::from_utf8(key.0.into())?)),
+                    b"freq" => Ok(Object::Freq(String::from_utf8(key.0.into())?)),
+                    _ => Err(CommandError::InvalidArgument(format!(
+                        "unknown subcommand '{}'",
+                        String::from_utf8_lossy(subcommand.as_ref())
+                    ))),
+                }
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid subcommand or key".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Dump {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dump"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(key) => Ok(Dump {
+                key: key.try_into()?,
+            }),
+            None => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Restore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // key, ttl and payload plus the optional REPLACE and ABSTTL flags
+        validate_command_range(&value, &["restore"], 3..=5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, ttl, payload) = match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(ttl), Some(payload)) => (
+                key.try_into()?,
+                i64::try_from(&ttl)?,
+                Vec::<u8>::try_from(payload)?,
+            ),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid Key, TTL or Payload".to_string(),
+                ))
+            }
+        };
+        let (mut replace, mut absttl) = (false, false);
+        for flag in args {
+            match flag {
+                RespFrame::BulkString(flag) if flag.as_ref().eq_ignore_ascii_case(b"replace") => {
+                    replace = true
+                }
+                RespFrame::BulkString(flag) if flag.as_ref().eq_ignore_ascii_case(b"absttl") => {
+                    absttl = true
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+            }
+        }
+        Ok(Restore {
+            key,
+            ttl,
+            payload,
+            replace,
+            absttl,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    };
+
+    use anyhow::Result;
+
+    use crate::{
+        backend::Backend,
+        cmd::{hmap::HGetAll, run, CommandExecutor, RESP_OK},
+        BulkString, NullBulkString, RespArray, RespFrame, SimpleError, SimpleString,
+    };
+
+    use super::{
+        dump_payload, Copy, Dump, DumpValue, Object, RandomKey, Restore, Scan, Touch, Unlink,
+    };
+
+    fn scan_all(backend: &Backend, pattern: Option<&str>) -> Vec<String> {
+        let mut cursor = 0;
+        let mut keys = vec![];
+        loop {
+            let cmd = Scan {
+                cursor,
+                pattern: pattern.map(|p| p.to_string()),
+                count: 7,
+            };
+            let RespFrame::Array(resp) = cmd.execute(backend) else {
+                panic!("scan must reply an array");
+            };
+            match (&resp[0], &resp[1]) {
+                (RespFrame::BulkString(next), RespFrame::Array(page)) => {
+                    cursor = String::from_utf8_lossy(next.as_ref()).parse().unwrap();
+                    for key in page.iter() {
+                        match key {
+                            RespFrame::BulkString(key) => {
+                                keys.push(String::from_utf8_lossy(key.as_ref()).to_string())
+                            }
+                            _ => panic!("keys must be bulk strings"),
+                        }
+                    }
+                }
+                _ => panic!("unexpected scan reply"),
+            }
+            if cursor == 0 {
+                return keys;
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"scan", b"0", b"MATCH", b"user:*", b"count", b"100"]);
+        let cmd = Scan::try_from(resp_arr)?;
+        assert_eq!(cmd.cursor, 0);
+        assert_eq!(cmd.pattern, Some("user:*".to_string()));
+        assert_eq!(cmd.count, 100);
+
+        let resp_arr = RespArray::command(&[b"scan", b"0", b"count"]);
+        assert!(Scan::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_visits_every_key_once() -> Result<()> {
+        let backend = Backend::default();
+        for i in 0..30 {
+            backend.set(format!("key{}", i), BulkString::new(b"v").into());
+        }
+        backend.hset(
+            "map".to_string(),
+            "field".to_string(),
+            BulkString::new(b"v").into(),
+        );
+
+        let keys = scan_all(&backend, None);
+        assert_eq!(keys.len(), 31);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 31);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_match() -> Result<()> {
+        let backend = Backend::default();
+        for i in 0..10 {
+            backend.set(format!("user:{}", i), BulkString::new(b"v").into());
+            backend.set(format!("session:{}", i), BulkString::new(b"v").into());
+        }
+
+        let keys = scan_all(&backend, Some("user:*"));
+        assert_eq!(keys.len(), 10);
+        assert!(keys.iter().all(|k| k.starts_with("user:")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomkey_command() -> Result<()> {
+        let backend = Backend::default();
+        let keys = ["foo", "bar", "baz"];
+        for key in keys {
+            backend.set(key.to_string(), BulkString::new(b"v").into());
+        }
+
+        for _ in 0..10 {
+            match RandomKey.execute(&backend) {
+                RespFrame::BulkString(key) => {
+                    assert!(keys.contains(&String::from_utf8_lossy(key.as_ref()).as_ref()))
+                }
+                frame => panic!("unexpected frame: {:?}", frame),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomkey_empty_backend() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(RandomKey.execute(&backend), NullBulkString.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"object", b"ENCODING", b"foo"]);
+        let cmd = Object::try_from(resp_arr)?;
+        assert_eq!(cmd, Object::Encoding("foo".to_string()));
+
+        let resp_arr = RespArray::command(&[b"object", b"idletime", b"foo"]);
+        let cmd = Object::try_from(resp_arr)?;
+        assert_eq!(cmd, Object::IdleTime("foo".to_string()));
+
+        let resp_arr = RespArray::command(&[b"object", b"nope", b"foo"]);
+        assert!(Object::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_encoding_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("num".to_string(), BulkString::new(b"12345").into());
+        backend.set("short".to_string(), BulkString::new(b"hello").into());
+        backend.set("long".to_string(), BulkString::new(vec![b'a'; 64]).into());
+        backend.hset(
+            "map".to_string(),
+            "field".to_string(),
+            BulkString::new(b"v").into(),
+        );
+
+        let encoding = |key: &str| Object::Encoding(key.to_string()).execute(&backend);
+        assert_eq!(encoding("num"), BulkString::new(b"int").into());
+        assert_eq!(encoding("short"), BulkString::new(b"embstr").into());
+        assert_eq!(encoding("long"), BulkString::new(b"raw").into());
+        assert_eq!(encoding("map"), BulkString::new(b"hashtable").into());
+        assert_eq!(
+            encoding("missing"),
+            SimpleError::new("ERR no such key").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_refcount_and_freq_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("k".to_string(), BulkString::new(b"v").into());
+        assert_eq!(
+            run::<Object>(&backend, &[b"object", b"REFCOUNT", b"k"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            run::<Object>(&backend, &[b"object", b"freq", b"k"])?,
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            run::<Object>(&backend, &[b"object", b"refcount", b"missing"])?,
+            SimpleError::new("ERR no such key").into()
+        );
+        assert_eq!(
+            run::<Object>(&backend, &[b"object", b"freq", b"missing"])?,
+            SimpleError::new("ERR no such key").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_idletime_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+
+        let idletime = |key: &str| Object::IdleTime(key.to_string()).execute(&backend);
+        assert_eq!(idletime("foo"), RespFrame::Integer(0));
+        assert_eq!(
+            idletime("missing"),
+            SimpleError::new("ERR no such key").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"copy", b"src", b"dst", b"REPLACE"]);
+        let cmd = Copy::try_from(resp_arr)?;
+        assert_eq!(cmd.source, "src");
+        assert_eq!(cmd.destination, "dst");
+        assert!(cmd.replace);
+
+        let resp_arr = RespArray::command(&[b"copy", b"src", b"dst", b"whatever"]);
+        assert!(Copy::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("src".to_string(), BulkString::new(b"hello").into());
+        backend.hset(
+            "map".to_string(),
+            "field".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        let copy = |source: &str, destination: &str, replace| {
+            Copy {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                replace,
+            }
+            .execute(&backend)
+        };
+
+        assert_eq!(copy("src", "dst", false), RespFrame::Integer(1));
+        assert_eq!(backend.get("dst"), Some(BulkString::new(b"hello").into()));
+
+        // the destination exists now
+        backend.set("src".to_string(), BulkString::new(b"world").into());
+        assert_eq!(copy("src", "dst", false), RespFrame::Integer(0));
+        assert_eq!(backend.get("dst"), Some(BulkString::new(b"hello").into()));
+        assert_eq!(copy("src", "dst", true), RespFrame::Integer(1));
+        assert_eq!(backend.get("dst"), Some(BulkString::new(b"world").into()));
+
+        // hashes are deep cloned
+        assert_eq!(copy("map", "map2", false), RespFrame::Integer(1));
+        backend.hset(
+            "map".to_string(),
+            "other".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        assert_eq!(backend.hgetall("map2").unwrap().len(), 1);
+
+        assert_eq!(copy("missing", "dst", true), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_touch_command() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"touch", b"foo", b"missing", b"map"]);
+        let cmd = Touch::try_from(resp_arr)?;
+        assert_eq!(cmd.keys, vec!["foo", "missing", "map"]);
+
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        backend.hset(
+            "map".to_string(),
+            "field".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unlink_command() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        for i in 0..1000 {
+            backend.hset(
+                "big".to_string(),
+                format!("field{}", i),
+                BulkString::new(b"v").into(),
+            );
+        }
+
+        let cmd = Unlink {
+            keys: vec!["foo".to_string(), "big".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(!backend.exists("foo"));
+        assert!(!backend.exists("big"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_restore_hash() -> Result<()> {
+        let backend = Backend::default();
+        for (field, value) in [("a", "1"), ("b", "two"), ("c", "3.5")] {
+            backend.hset(
+                "map".to_string(),
+                field.to_string(),
+                BulkString::new(value).into(),
+            );
+        }
+        let hgetall = || run::<HGetAll>(&backend, &[b"hgetall", b"map"]);
+        let before = hgetall()?;
+
+        let RespFrame::BulkString(payload) = run::<Dump>(&backend, &[b"dump", b"map"])? else {
+            panic!("dump must reply a bulk string");
+        };
+        assert!(backend.del("map"));
+        let ret = run::<Restore>(&backend, &[b"restore", b"map", b"0", payload.as_ref()])?;
+        assert_eq!(
+            ret,
+            RespFrame::SimpleString(SimpleString::new("OK".to_string()))
+        );
+        assert_eq!(hgetall()?, before);
+        assert_eq!(backend.ttl("map"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_restore_string_with_ttl() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"bar").into());
+        let RespFrame::BulkString(payload) = run::<Dump>(&backend, &[b"dump", b"foo"])? else {
+            panic!("dump must reply a bulk string");
+        };
+
+        // the key still exists
+        let ret = run::<Restore>(&backend, &[b"restore", b"foo", b"0", payload.as_ref()])?;
+        assert_eq!(
+            ret,
+            SimpleError::new("BUSYKEY Target key name already exists.").into()
+        );
+        let ret = run::<Restore>(
+            &backend,
+            &[b"restore", b"copy", b"5000", payload.as_ref(), b"REPLACE"],
+        )?;
+        assert_eq!(
+            ret,
+            RespFrame::SimpleString(SimpleString::new("OK".to_string()))
+        );
+        assert_eq!(backend.get("copy"), Some(BulkString::new(b"bar").into()));
+        assert!(backend.ttl("copy").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_absttl() -> Result<()> {
+        let backend = Backend::default();
+        let payload = dump_payload(DumpValue::String(BulkString::new(b"bar").into()));
+        let deadline = backend.unix_millis_of(Instant::now() + Duration::from_secs(100));
+        let deadline = deadline.to_string();
+        let ret = run::<Restore>(
+            &backend,
+            &[
+                b"restore",
+                b"k",
+                deadline.as_bytes(),
+                &payload,
+                b"ABSTTL",
+                b"replace",
+            ],
+        )?;
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(backend
+            .ttl("k")
+            .is_some_and(|ttl| ttl > Duration::from_secs(99)));
+
+        assert!(run::<Restore>(&backend, &[b"restore", b"k", b"0", &payload, b"abs"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_replace_is_never_partial() -> Result<()> {
+        let backend = Backend::default();
+        let fields = |value: &str| {
+            ["a", "b", "c"]
+                .map(|field| (field.to_string(), BulkString::new(value).into()))
+                .to_vec()
+        };
+        backend.set_hash_with_deadline("h".to_string(), fields("old"), None);
+        let payload = dump_payload(DumpValue::Hash(fields("new")));
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    let ret = run::<Restore>(
+                        &backend,
+                        &[b"restore", b"h", b"5000", &payload, b"replace"],
+                    );
+                    assert_eq!(ret.unwrap(), RESP_OK.clone());
+                }
+            });
+            for _ in 0..1000 {
+                // always a whole hash, never a missing key or a missing field
+                assert_eq!(backend.hgetall("h").map(|h| h.len()), Some(3));
+            }
+        });
+        assert!(backend.ttl("h").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_restore_errors() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(
+            run::<Dump>(&backend, &[b"dump", b"missing"])?,
+            NullBulkString.into()
+        );
+        assert_eq!(
+            run::<Restore>(&backend, &[b"restore", b"k", b"0", b"garbage"])?,
+            SimpleError::new("ERR DUMP payload version or checksum are wrong").into()
+        );
+        assert_eq!(
+            run::<Restore>(&backend, &[b"restore", b"k", b"-1", b"garbage"])?,
+            SimpleError::new("ERR Invalid TTL value, must be >= 0").into()
+        );
+        assert!(!backend.exists("k"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_oversized_declared_length() -> Result<()> {
+        let backend = Backend::default();
+        for payload in [
+            &b"*999999999999999\r\n"[..],
+            b"*2\r\n$6\r\nstring\r\n*999999999999999\r\n",
+            b"*2\r\n$6\r\nstring\r\n$999999999999999\r\nx\r\n",
+        ] {
+            assert_eq!(
+                run::<Restore>(&backend, &[b"restore", b"k", b"0", payload])?,
+                SimpleError::new("ERR DUMP payload version or checksum are wrong").into()
+            );
+        }
+        assert!(!backend.exists("k"));
+        Ok(())
+    }
+}
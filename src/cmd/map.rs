@@ -1,6 +1,26 @@
-use crate::{backend::Backend, RespArray, RespFrame, RespNull};
+use std::time::{Duration, Instant};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::{backend::Backend, BulkString, NullBulkString, RespArray, RespFrame, SimpleError};
+
+use super::{
+    ensure_string, extract_args, validate_command, validate_command_range, Command, CommandEntry,
+    CommandError, CommandExecutor, RESP_OK,
+};
+
+// strings are capped at 512MB like redis
+const MAX_STRING_LEN: u64 = 512 * 1024 * 1024;
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("get", false, |v| v.try_into().map(Command::Get)),
+    ("set", true, |v| v.try_into().map(Command::Set)),
+    ("setex", true, |v| v.try_into().map(Command::SetEx)),
+    ("psetex", true, |v| v.try_into().map(Command::PSetEx)),
+    ("getex", true, |v| v.try_into().map(Command::GetEx)),
+    ("getrange", false, |v| v.try_into().map(Command::GetRange)),
+    ("setrange", true, |v| v.try_into().map(Command::SetRange)),
+    ("append", true, |v| v.try_into().map(Command::Append)),
+];
 
 #[derive(Debug, PartialEq, PartialOrd)]
 pub struct Get {
@@ -11,24 +31,246 @@ pub struct Get {
 pub struct Set {
     key: String,
     value: RespFrame,
+    expiry: SetExpiry,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SetExpiry {
+    // overwriting the key clears its expiry
+    Clear,
+    // KEEPTTL
+    Keep,
+    // PXAT, a unix time in milliseconds
+    At { unix_millis: i64 },
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct SetEx {
+    key: String,
+    seconds: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PSetEx {
+    key: String,
+    milliseconds: i64,
+    value: RespFrame,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct GetEx {
+    key: String,
+    option: GetExOption,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+enum GetExOption {
+    // leave the current expiry as is
+    Keep,
+    Expire { milliseconds: i64 },
+    Persist,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Append {
+    key: String,
+    value: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct SetRange {
+    key: String,
+    offset: i64,
+    value: Vec<u8>,
 }
 
 impl CommandExecutor for Get {
     fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
         match backend.get(&self.key) {
             Some(v) => v,
-            None => RespFrame::Null(RespNull),
+            None => NullBulkString.into(),
         }
     }
 }
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+        match self.expiry {
+            SetExpiry::Clear => backend.set(self.key, self.value),
+            SetExpiry::Keep => backend.set_keepttl(self.key, self.value),
+            SetExpiry::At { unix_millis } if unix_millis <= 0 => {
+                return RespFrame::SimpleError(SimpleError::new(
+                    "ERR invalid expire time in 'set' command",
+                ));
+            }
+            SetExpiry::At { unix_millis } => {
+                let deadline = backend.deadline_at(unix_millis);
+                backend.set_with_deadline(self.key, self.value, Some(deadline));
+            }
+        }
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for SetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        set_with_expiry(
+            backend,
+            "setex",
+            self.key,
+            self.value,
+            self.seconds.saturating_mul(1000),
+        )
+    }
+}
+
+impl CommandExecutor for PSetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        set_with_expiry(backend, "psetex", self.key, self.value, self.milliseconds)
+    }
+}
+
+fn set_with_expiry(
+    backend: &Backend,
+    name: &str,
+    key: String,
+    value: RespFrame,
+    milliseconds: i64,
+) -> RespFrame {
+    if milliseconds <= 0 {
+        return RespFrame::SimpleError(SimpleError::new(format!(
+            "ERR invalid expire time in '{}' command",
+            name
+        )));
+    }
+    let deadline = Instant::now() + Duration::from_millis(milliseconds as u64);
+    backend.set_with_deadline(key, value, Some(deadline));
+    RESP_OK.clone()
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        let value = match backend.get(&self.key) {
+            Some(v) => v,
+            None => return NullBulkString.into(),
+        };
+        match self.option {
+            GetExOption::Keep => {}
+            GetExOption::Expire { milliseconds } if milliseconds <= 0 => {
+                return RespFrame::SimpleError(SimpleError::new(
+                    "ERR invalid expire time in 'getex' command",
+                ));
+            }
+            GetExOption::Expire { milliseconds } => {
+                let deadline = Instant::now() + Duration::from_millis(milliseconds as u64);
+                backend.expire(&self.key, deadline);
+            }
+            GetExOption::Persist => {
+                backend.persist(&self.key);
+            }
+        }
+        value
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        let len = backend.with_string_mut(self.key, true, |value| {
+            value.extend_from_slice(&self.value);
+            value.len()
+        });
+        RespFrame::Integer(len.unwrap_or_default() as i64)
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        let value = match backend.get(&self.key) {
+            Some(RespFrame::BulkString(v)) => v.0,
+            _ => return RespFrame::BulkString(BulkString::new(vec![])),
+        };
+
+        // inclusive range, negative indexes count from the end
+        let len = value.len() as i64;
+        if len == 0 || (self.start < 0 && self.end < 0 && self.start > self.end) {
+            return RespFrame::BulkString(BulkString::new(vec![]));
+        }
+        let start = normalize_index(self.start, len).max(0);
+        let end = normalize_index(self.end, len).clamp(0, len - 1);
+        if start > end {
+            return RespFrame::BulkString(BulkString::new(vec![]));
+        }
+        RespFrame::BulkString(BulkString::from(value.slice(start as usize..=end as usize)))
+    }
+}
+
+// negative indexes count from the end of a value of length `len`
+pub(super) fn normalize_index(index: i64, len: i64) -> i64 {
+    if index < 0 {
+        len + index
+    } else {
+        index
+    }
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        if self.offset < 0 {
+            return RespFrame::SimpleError(SimpleError::new("ERR offset is out of range"));
+        }
+        // checked before padding, a huge offset would otherwise allocate that many bytes
+        if self.offset as u64 + self.value.len() as u64 > MAX_STRING_LEN {
+            return RespFrame::SimpleError(SimpleError::new(
+                "ERR string exceeds maximum allowed size (proto-max-bulk-len)",
+            ));
+        }
+        // an empty value neither creates nor touches the key
+        if self.value.is_empty() {
+            let len = match backend.get(&self.key) {
+                Some(RespFrame::BulkString(v)) => v.as_ref().len(),
+                _ => 0,
+            };
+            return RespFrame::Integer(len as i64);
+        }
+
+        // edited in place, so the key keeps its expiry
+        let len = backend.with_string_mut(self.key, true, |current| {
+            // zero-pad when writing past the end of the current value
+            let offset = self.offset as usize;
+            let end = offset + self.value.len();
+            if current.len() < end {
+                current.resize(end, 0);
+            }
+            current[offset..end].copy_from_slice(&self.value);
+            current.len()
+        });
+        RespFrame::Integer(len.unwrap_or_default() as i64)
+    }
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
@@ -38,7 +280,7 @@ impl TryFrom<RespArray> for Get {
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
+                key: String::from_utf8(key.0.into())?,
             }),
             _ => Err(CommandError::InvalidArgument("Invalid Key".to_string())),
         }
@@ -49,16 +291,171 @@ impl TryFrom<RespArray> for Set {
     type Error = CommandError;
 
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&v, &["set"], 2)?;
+        validate_command_range(&v, &["set"], 2..=4)?;
 
         let mut args = extract_args(v, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
-                key: String::from_utf8(key.0)?,
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => {
+                (String::from_utf8(key.0.into())?, value)
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid Key or Value".to_string(),
+                ))
+            }
+        };
+        let expiry = match (args.next(), args.next()) {
+            (None, None) => SetExpiry::Clear,
+            (Some(RespFrame::BulkString(option)), None)
+                if option.as_ref().eq_ignore_ascii_case(b"keepttl") =>
+            {
+                SetExpiry::Keep
+            }
+            (Some(RespFrame::BulkString(option)), Some(time))
+                if option.as_ref().eq_ignore_ascii_case(b"pxat") =>
+            {
+                SetExpiry::At {
+                    unix_millis: i64::try_from(&time)?,
+                }
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(Set { key, value, expiry })
+    }
+}
+
+impl TryFrom<RespArray> for SetEx {
+    type Error = CommandError;
+
+    fn try_from(v: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&v, &["setex"], 3)?;
+
+        let mut args = extract_args(v, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(seconds), Some(value)) => Ok(SetEx {
+                key: key.try_into()?,
+                seconds: i64::try_from(&seconds)?,
+                value,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key, Seconds or Value".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PSetEx {
+    type Error = CommandError;
+
+    fn try_from(v: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&v, &["psetex"], 3)?;
+
+        let mut args = extract_args(v, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(milliseconds), Some(value)) => Ok(PSetEx {
+                key: key.try_into()?,
+                milliseconds: i64::try_from(&milliseconds)?,
                 value,
             }),
             _ => Err(CommandError::InvalidArgument(
-                "Invalid Key or Value".to_string(),
+                "Invalid Key, Milliseconds or Value".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+
+    fn try_from(v: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&v, &["getex"], 1..=3)?;
+
+        let mut args = extract_args(v, 1)?.into_iter();
+        let key = match args.next() {
+            Some(key) => key.try_into()?,
+            None => return Err(CommandError::InvalidArgument("Invalid Key".to_string())),
+        };
+        let option = match (args.next(), args.next()) {
+            (None, None) => GetExOption::Keep,
+            (Some(RespFrame::BulkString(option)), None)
+                if option.as_ref().eq_ignore_ascii_case(b"persist") =>
+            {
+                GetExOption::Persist
+            }
+            // the option is checked first, the time is only parsed for EX and PX
+            (Some(RespFrame::BulkString(option)), Some(time)) => {
+                match option.as_ref().to_ascii_lowercase().as_slice() {
+                    b"ex" => GetExOption::Expire {
+                        milliseconds: i64::try_from(&time)?.saturating_mul(1000),
+                    },
+                    b"px" => GetExOption::Expire {
+                        milliseconds: i64::try_from(&time)?,
+                    },
+                    _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+                }
+            }
+            _ => return Err(CommandError::InvalidArgument("syntax error".to_string())),
+        };
+        Ok(GetEx { key, option })
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(value)) => Ok(Append {
+                key: key.try_into()?,
+                value: value.try_into()?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+
+    fn try_from(v: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&v, &["getrange"], 3)?;
+
+        let mut args = extract_args(v, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(start), Some(end)) => Ok(GetRange {
+                key: key.try_into()?,
+                start: i64::try_from(&start)?,
+                end: i64::try_from(&end)?,
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key or Range".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+
+    fn try_from(v: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&v, &["setrange"], 3)?;
+
+        let mut args = extract_args(v, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(offset)),
+                Some(RespFrame::BulkString(value)),
+            ) => Ok(SetRange {
+                key: String::from_utf8(key.0.into())?,
+                offset: String::from_utf8(offset.0.into())?.parse()?,
+                value: value.0.into(),
+            }),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid Key, Offset or Value".to_string(),
             )),
         }
     }
@@ -69,13 +466,15 @@ mod tests {
     use crate::{
         backend::Backend,
         cmd::{
-            map::{Get, Set},
-            CommandExecutor, RESP_OK,
+            expire::{Expire, Ttl},
+            map::{Append, Get, GetEx, GetRange, PSetEx, Set, SetEx, SetExpiry, SetRange},
+            run, CommandExecutor, RESP_OK,
         },
-        BulkString, RespArray, RespDecode, RespFrame,
+        BulkString, NullBulkString, RespArray, RespDecode, RespFrame, SimpleError,
     };
     use anyhow::{Ok, Result};
     use bytes::BytesMut;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn test_get_from_resp_array() -> Result<()> {
@@ -107,11 +506,12 @@ mod tests {
 
     #[test]
     fn test_set_get_command() -> Result<()> {
-        let backend = Backend::new();
+        let backend = Backend::default();
 
         let set_cmd = Set {
             key: "hello".to_string(),
             value: RespFrame::BulkString(BulkString::new(b"world")),
+            expiry: SetExpiry::Clear,
         };
 
         let resp = set_cmd.execute(&backend);
@@ -126,4 +526,349 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_clears_ttl_unless_keepttl() -> Result<()> {
+        let backend = Backend::default();
+        run::<Set>(&backend, &[b"set", b"k", b"v1"])?;
+        run::<Expire>(&backend, &[b"expire", b"k", b"100"])?;
+
+        // a plain SET drops the expiry
+        assert_eq!(
+            run::<Set>(&backend, &[b"set", b"k", b"v2"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            run::<Ttl>(&backend, &[b"ttl", b"k"])?,
+            RespFrame::Integer(-1)
+        );
+
+        run::<Expire>(&backend, &[b"expire", b"k", b"100"])?;
+        assert_eq!(
+            run::<Set>(&backend, &[b"set", b"k", b"v3", b"KEEPTTL"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            run::<Ttl>(&backend, &[b"ttl", b"k"])?,
+            RespFrame::Integer(100)
+        );
+        assert_eq!(backend.get("k"), Some(BulkString::new(b"v3").into()));
+
+        assert!(run::<Set>(&backend, &[b"set", b"k", b"v4", b"keep"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_pxat() -> Result<()> {
+        let backend = Backend::default();
+        let deadline = backend.unix_millis_of(Instant::now() + Duration::from_secs(100));
+        let deadline = deadline.to_string();
+        assert_eq!(
+            run::<Set>(
+                &backend,
+                &[b"set", b"k", b"v", b"PXAT", deadline.as_bytes()]
+            )?,
+            RESP_OK.clone()
+        );
+        let ttl = run::<Ttl>(&backend, &[b"ttl", b"k"])?;
+        assert!(matches!(ttl, RespFrame::Integer(99..=100)), "{:?}", ttl);
+
+        assert_eq!(
+            run::<Set>(&backend, &[b"set", b"k", b"v", b"pxat", b"0"])?,
+            SimpleError::new("ERR invalid expire time in 'set' command").into()
+        );
+        assert!(run::<Set>(&backend, &[b"set", b"k", b"v", b"pxat"]).is_err());
+        assert!(run::<Set>(&backend, &[b"set", b"k", b"v", b"keepttl", b"1"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$8\r\ngetrange\r\n$3\r\nkey\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result = GetRange::try_from(frame)?;
+        assert_eq!(result.key, "key");
+        assert_eq!(result.start, 0);
+        assert_eq!(result.end, -1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange_negative_range() -> Result<()> {
+        let backend = Backend::default();
+        backend.set(
+            "key".to_string(),
+            RespFrame::BulkString(BulkString::new(b"This is a string")),
+        );
+
+        let getrange = |start, end| {
+            GetRange {
+                key: "key".to_string(),
+                start,
+                end,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getrange(0, 3), BulkString::new(b"This").into());
+        assert_eq!(getrange(-3, -1), BulkString::new(b"ing").into());
+        assert_eq!(getrange(0, -1), BulkString::new(b"This is a string").into());
+        assert_eq!(getrange(10, 100), BulkString::new(b"string").into());
+        assert_eq!(getrange(-1, -3), BulkString::new(b"").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setrange_zero_padding() -> Result<()> {
+        let backend = Backend::default();
+
+        let resp = SetRange {
+            key: "key".to_string(),
+            offset: 6,
+            value: b"Redis".to_vec(),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("key"),
+            Some(BulkString::new(b"\0\0\0\0\0\0Redis").into())
+        );
+
+        let resp = SetRange {
+            key: "key".to_string(),
+            offset: 0,
+            value: b"Hello ".to_vec(),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("key"),
+            Some(BulkString::new(b"Hello Redis").into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ranges_use_byte_offsets() -> Result<()> {
+        let backend = Backend::default();
+        // "héllo wörld", é and ö are two bytes each
+        backend.set("k".to_string(), BulkString::new("héllo wörld").into());
+
+        // starts and ends inside a multibyte character, the raw bytes come back
+        assert_eq!(
+            run::<GetRange>(&backend, &[b"getrange", b"k", b"2", b"8"])?,
+            BulkString::new(b"\xa9llo w\xc3".to_vec()).into()
+        );
+        assert_eq!(
+            run::<GetRange>(&backend, &[b"getrange", b"k", b"-4", b"-4"])?,
+            BulkString::new(b"\xb6".to_vec()).into()
+        );
+
+        // overwriting half of é leaves an invalid utf-8 value, which is fine for a string
+        assert_eq!(
+            run::<SetRange>(&backend, &[b"setrange", b"k", b"2", b"E"])?,
+            RespFrame::Integer(13)
+        );
+        assert_eq!(
+            backend.get("k"),
+            Some(BulkString::new(b"h\xc3Ello w\xc3\xb6rld".to_vec()).into())
+        );
+
+        assert_eq!(
+            run::<SetRange>(&backend, &[b"setrange", b"k", b"536870912", b"x"])?,
+            SimpleError::new("ERR string exceeds maximum allowed size (proto-max-bulk-len)").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_setex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nsetex\r\n$5\r\nhello\r\n$2\r\n10\r\n$5\r\nworld\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let result = SetEx::try_from(frame)?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.value, BulkString::new(b"world").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setex_psetex_command() -> Result<()> {
+        let backend = Backend::default();
+
+        let resp = SetEx {
+            key: "hello".to_string(),
+            seconds: 10,
+            value: BulkString::new(b"world").into(),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RESP_OK.clone());
+        assert_eq!(backend.get("hello"), Some(BulkString::new(b"world").into()));
+        let ttl = backend.ttl("hello").unwrap();
+        assert!(ttl.as_millis() > 9_000 && ttl.as_millis() <= 10_000);
+
+        let resp = PSetEx {
+            key: "foo".to_string(),
+            milliseconds: 1_500,
+            value: BulkString::new(b"bar").into(),
+        }
+        .execute(&backend);
+        assert_eq!(resp, RESP_OK.clone());
+        assert_eq!(backend.get("foo"), Some(BulkString::new(b"bar").into()));
+        let ttl = backend.ttl("foo").unwrap();
+        assert!(ttl.as_millis() > 1_000 && ttl.as_millis() <= 1_500);
+        Ok(())
+    }
+
+    #[test]
+    fn test_setex_value_never_visible_without_ttl() {
+        let backend = Backend::default();
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    SetEx {
+                        key: "k".to_string(),
+                        seconds: 10,
+                        value: BulkString::new(b"v").into(),
+                    }
+                    .execute(&backend);
+                }
+            });
+            for _ in 0..1000 {
+                if backend.exists("k") {
+                    assert!(backend.ttl("k").is_some());
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_setex_invalid_expire_time() -> Result<()> {
+        let backend = Backend::default();
+
+        let resp = SetEx {
+            key: "hello".to_string(),
+            seconds: 0,
+            value: BulkString::new(b"world").into(),
+        }
+        .execute(&backend);
+        assert_eq!(
+            resp,
+            SimpleError::new("ERR invalid expire time in 'setex' command").into()
+        );
+
+        let resp = PSetEx {
+            key: "hello".to_string(),
+            milliseconds: -5,
+            value: BulkString::new(b"world").into(),
+        }
+        .execute(&backend);
+        assert_eq!(
+            resp,
+            SimpleError::new("ERR invalid expire time in 'psetex' command").into()
+        );
+        assert_eq!(backend.get("hello"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_expiry_options() -> Result<()> {
+        let backend = Backend::default();
+        let value = RespFrame::BulkString(BulkString::new(b"world"));
+        backend.set("hello".to_string(), value.clone());
+
+        let getex = |parts: &[&[u8]]| -> Result<RespFrame> {
+            let cmd = GetEx::try_from(RespArray::command(parts))?;
+            Ok(cmd.execute(&backend))
+        };
+
+        // no option leaves the key persistent
+        assert_eq!(getex(&[b"getex", b"hello"])?, value);
+        assert_eq!(backend.ttl("hello"), None);
+
+        assert_eq!(getex(&[b"getex", b"hello", b"EX", b"100"])?, value);
+        let ttl = backend.ttl("hello").unwrap();
+        assert!(ttl > Duration::from_secs(99) && ttl <= Duration::from_secs(100));
+
+        // no option leaves the ttl intact
+        assert_eq!(getex(&[b"getex", b"hello"])?, value);
+        assert!(backend.ttl("hello").is_some());
+
+        assert_eq!(getex(&[b"getex", b"hello", b"px", b"5000"])?, value);
+        assert!(backend.ttl("hello").unwrap() <= Duration::from_millis(5000));
+
+        assert_eq!(getex(&[b"getex", b"hello", b"persist"])?, value);
+        assert_eq!(backend.ttl("hello"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_errors() -> Result<()> {
+        let backend = Backend::default();
+        let missing = GetEx::try_from(RespArray::command(&[b"getex", b"nope", b"ex", b"10"]))?;
+        assert_eq!(missing.execute(&backend), NullBulkString.into());
+        assert!(!backend.exists("nope"));
+
+        backend.set("hello".to_string(), BulkString::new(b"world").into());
+        let zero = GetEx::try_from(RespArray::command(&[b"getex", b"hello", b"ex", b"0"]))?;
+        assert_eq!(
+            zero.execute(&backend),
+            SimpleError::new("ERR invalid expire time in 'getex' command").into()
+        );
+
+        assert!(GetEx::try_from(RespArray::command(&[b"getex", b"hello", b"keep"])).is_err());
+        assert!(GetEx::try_from(RespArray::command(&[b"getex", b"hello", b"ex"])).is_err());
+
+        // an unknown option is a syntax error whatever follows it
+        for option in [&b"FOO"[..], b"PERSIST"] {
+            let err = GetEx::try_from(RespArray::command(&[b"getex", b"hello", option, b"x"]))
+                .unwrap_err();
+            assert_eq!(err.to_string(), "Invalid argument: syntax error");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_command() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(
+            run::<Append>(&backend, &[b"append", b"k", b"Hello"])?,
+            RespFrame::Integer(5)
+        );
+        assert_eq!(
+            run::<Append>(&backend, &[b"append", b"k", b" World"])?,
+            RespFrame::Integer(11)
+        );
+        assert_eq!(
+            backend.get("k"),
+            Some(BulkString::new(b"Hello World").into())
+        );
+
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        let wrongtype: RespFrame =
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into();
+        assert_eq!(
+            run::<Append>(&backend, &[b"append", b"h", b"x"])?,
+            wrongtype
+        );
+        assert_eq!(run::<Get>(&backend, &[b"get", b"h"])?, wrongtype);
+
+        // SET replaces the hash instead of failing
+        assert_eq!(
+            run::<Set>(&backend, &[b"set", b"h", b"s"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(backend.hget("h", "f"), None);
+        Ok(())
+    }
 }
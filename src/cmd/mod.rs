@@ -1,25 +1,54 @@
+use std::{collections::HashMap, ops::RangeBounds};
+
+use bitops::{BitCount, BitOp, GetBit, SetBit};
+use connection::{Client, Hello, Ping};
 use enum_dispatch::enum_dispatch;
-use hmap::{HGet, HGetAll, HSet};
+use expire::{Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Ttl};
+use hmap::{HExists, HGet, HGetAll, HIncrBy, HIncrByFloat, HScan, HSet, HSetNx};
+use keyspace::{Copy, DbSize, Dump, Object, RandomKey, Restore, Scan, Touch, Unlink};
 use lazy_static::lazy_static;
-use map::{Get, Set};
+use map::{Append, Get, GetEx, GetRange, PSetEx, Set, SetEx, SetRange};
+use numeric::{Decr, Incr, IncrByFloat};
+use server::{CommandInfo, Debug, FullSync, Info, Monitor, ReplicaOf, Reset, SlowLog, Wait};
 use thiserror::Error;
 use tracing::warn;
 
-use crate::{Backend, RespArray, RespFrame, SimpleError, SimpleString};
+use crate::{Backend, BulkString, RespArray, RespFrame, SimpleError, SimpleString};
 
+mod bitops;
+mod connection;
+mod expire;
 mod hmap;
+mod keyspace;
 mod map;
+mod numeric;
+mod server;
+
+pub use connection::ConnectionContext;
+pub(crate) use keyspace::restore_commands;
 
 lazy_static! {
     static ref RESP_OK: RespFrame = RespFrame::SimpleString(SimpleString("OK".into()));
 }
 
 #[derive(Debug)]
-pub struct Unrecongnized(String);
+pub struct Unrecognized(String);
+
+// a known command whose arguments could not be parsed
+#[derive(Debug)]
+pub struct Invalid(CommandError);
 
 #[enum_dispatch]
 pub trait CommandExecutor {
     fn execute(self, backend: &Backend) -> RespFrame;
+
+    /// Called by the connection handler, commands that await or use the connection state override it.
+    async fn execute_async(self, backend: &Backend, _context: &mut ConnectionContext) -> RespFrame
+    where
+        Self: Sized,
+    {
+        backend.execute_read(|| self.execute(backend))
+    }
 }
 
 #[enum_dispatch(CommandExecutor)]
@@ -27,11 +56,59 @@ pub trait CommandExecutor {
 pub enum Command {
     Get(Get),
     Set(Set),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
+    GetEx(GetEx),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Incr(Incr),
+    Decr(Decr),
+    IncrByFloat(IncrByFloat),
+    Append(Append),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    BitCount(BitCount),
+    BitOp(BitOp),
     HGet(HGet),
     HSet(HSet),
+    HSetNx(HSetNx),
     HGetAll(HGetAll),
+    HExists(HExists),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HScan(HScan),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    Scan(Scan),
+    RandomKey(RandomKey),
+    DbSize(DbSize),
+    Object(Object),
+    Copy(Copy),
+    Touch(Touch),
+    Unlink(Unlink),
+    Dump(Dump),
+    Restore(Restore),
+    Wait(Wait),
+    Monitor(Monitor),
+    Reset(Reset),
+    CommandInfo(CommandInfo),
+    Info(Info),
+    SlowLog(SlowLog),
+    ReplicaOf(ReplicaOf),
+    FullSync(FullSync),
+    Ping(Ping),
+    Hello(Hello),
+    Client(Client),
+    Debug(Debug),
+    Invalid(Invalid),
     // identify unknown command
-    Unrecongnized(Unrecongnized),
+    Unrecognized(Unrecognized),
 }
 
 #[derive(Debug, Error)]
@@ -40,8 +117,176 @@ pub enum CommandError {
     InvalidCommand(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("wrong number of arguments for '{0}' command")]
+    WrongArity(String),
     #[error("Utf8 error: {0}")]
     FromUTF8Error(#[from] std::string::FromUtf8Error),
+    #[error("Parse int error: {0}")]
+    ParseIntError(#[from] std::num::ParseIntError),
+    #[error("Parse float error: {0}")]
+    ParseFloatError(#[from] std::num::ParseFloatError),
+}
+
+// argument coercion shared by the command parsers, bulk strings are parsed as needed
+impl TryFrom<RespFrame> for String {
+    type Error = CommandError;
+
+    fn try_from(v: RespFrame) -> Result<Self, Self::Error> {
+        match v {
+            RespFrame::BulkString(s) => Ok(String::from_utf8(s.0.into())?),
+            RespFrame::SimpleString(s) => Ok(s.0),
+            v => Err(unexpected_type("string", &v)),
+        }
+    }
+}
+
+impl TryFrom<RespFrame> for Vec<u8> {
+    type Error = CommandError;
+
+    fn try_from(v: RespFrame) -> Result<Self, Self::Error> {
+        match v {
+            RespFrame::BulkString(s) => Ok(s.0.into()),
+            RespFrame::SimpleString(s) => Ok(s.0.into_bytes()),
+            v => Err(unexpected_type("bytes", &v)),
+        }
+    }
+}
+
+// enum_dispatch already derives the owned TryInto for the numeric variants,
+// so numbers are extracted from a reference instead
+impl TryFrom<&RespFrame> for i64 {
+    type Error = CommandError;
+
+    fn try_from(v: &RespFrame) -> Result<Self, Self::Error> {
+        match v {
+            RespFrame::Integer(i) => Ok(*i),
+            RespFrame::BulkString(s) => Ok(String::from_utf8(s.0.to_vec())?.parse()?),
+            v => Err(unexpected_type("integer", v)),
+        }
+    }
+}
+
+impl TryFrom<&RespFrame> for f64 {
+    type Error = CommandError;
+
+    fn try_from(v: &RespFrame) -> Result<Self, Self::Error> {
+        match v {
+            RespFrame::Double(d) => Ok(*d),
+            RespFrame::Integer(i) => Ok(*i as f64),
+            RespFrame::BulkString(s) => Ok(String::from_utf8(s.0.to_vec())?.parse()?),
+            v => Err(unexpected_type("float", v)),
+        }
+    }
+}
+
+fn unexpected_type(expected: &str, got: &RespFrame) -> CommandError {
+    CommandError::InvalidArgument(format!("expected {}, got {:?}", expected, got))
+}
+
+// every supported command grouped by module, the single source for dispatch, COMMAND LIST
+// and which commands are propagated to replicas
+const COMMAND_TABLES: &[&[CommandEntry]] = &[
+    map::COMMANDS,
+    numeric::COMMANDS,
+    bitops::COMMANDS,
+    hmap::COMMANDS,
+    expire::COMMANDS,
+    keyspace::COMMANDS,
+    server::COMMANDS,
+    connection::COMMANDS,
+];
+
+lazy_static! {
+    // name -> table entry, built once from the module tables
+    static ref REGISTRY: HashMap<&'static [u8], &'static CommandEntry> =
+        commands().map(|entry| (entry.0.as_bytes(), entry)).collect();
+}
+
+/// Every supported command with its parser, in registration order.
+fn commands() -> impl Iterator<Item = &'static CommandEntry> {
+    COMMAND_TABLES.iter().flat_map(|table| table.iter())
+}
+
+type CommandParser = fn(RespArray) -> Result<Command, CommandError>;
+
+// name, whether it modifies the keyspace and is propagated to replicas when it succeeds, parser
+type CommandEntry = (&'static str, bool, CommandParser);
+
+pub(crate) fn is_write(name: &str) -> bool {
+    REGISTRY
+        .get(name.as_bytes())
+        .is_some_and(|(_, write, _)| *write)
+}
+
+/// A write that succeeded here, as it is sent to replicas.
+///
+/// Relative expiries become the deadline the key got here, so the time the command spends
+/// on its way to a replica does not extend them.
+pub(crate) fn replicated_write(args: &RespArray, backend: &Backend) -> RespArray {
+    let (Some(RespFrame::BulkString(name)), Some(RespFrame::BulkString(key))) =
+        (args.first(), args.get(1))
+    else {
+        return args.clone();
+    };
+    // without a deadline there is nothing relative to rewrite, e.g. a key that expired at once
+    let Some(deadline) = std::str::from_utf8(key.as_ref())
+        .ok()
+        .and_then(|key| backend.expire_time(key))
+    else {
+        return args.clone();
+    };
+    let deadline = deadline.to_string();
+    match name.as_ref().to_ascii_lowercase().as_slice() {
+        // GETEX without EX or PX leaves the deadline alone
+        b"getex" if args.len() != 4 => args.clone(),
+        b"expire" | b"pexpire" | b"getex" => {
+            RespArray::command(&[b"pexpireat", key.as_ref(), deadline.as_bytes()])
+        }
+        b"setex" | b"psetex" => match args.get(3) {
+            Some(RespFrame::BulkString(value)) => RespArray::command(&[
+                b"set",
+                key.as_ref(),
+                value.as_ref(),
+                b"pxat",
+                deadline.as_bytes(),
+            ]),
+            _ => args.clone(),
+        },
+        b"restore" => {
+            let mut parts = args.to_vec();
+            if let Some(ttl) = parts.get_mut(2) {
+                *ttl = BulkString::new(deadline).into();
+            }
+            let absolute = parts.iter().skip(4).any(|part| {
+                matches!(part, RespFrame::BulkString(flag) if flag.as_ref().eq_ignore_ascii_case(b"absttl"))
+            });
+            if !absolute {
+                parts.push(BulkString::new(b"absttl").into());
+            }
+            RespArray::new(parts)
+        }
+        _ => args.clone(),
+    }
+}
+
+// command names are case insensitive, only names with uppercase letters are copied
+fn lookup(name: &[u8]) -> Option<&'static CommandEntry> {
+    if name.iter().any(u8::is_ascii_uppercase) {
+        REGISTRY.get(name.to_ascii_lowercase().as_slice()).copied()
+    } else {
+        REGISTRY.get(name).copied()
+    }
+}
+
+/// Name of the supported command a request frame invokes, used to key the command stats.
+pub(crate) fn command_name(frame: &RespFrame) -> Option<&'static str> {
+    match frame {
+        RespFrame::Array(arr) => match arr.first() {
+            Some(RespFrame::BulkString(cmd)) => lookup(cmd.as_ref()).map(|(name, _, _)| *name),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 impl TryFrom<RespFrame> for Command {
@@ -56,21 +301,18 @@ impl TryFrom<RespFrame> for Command {
 
         match resp_arr.first() {
             Some(RespFrame::BulkString(ref cmd)) => {
-                let res = match cmd.as_ref() {
-                    b"get" => resp_arr.try_into().map(Command::Get),
-                    b"set" => resp_arr.try_into().map(Command::Set),
-                    b"hget" => resp_arr.try_into().map(Command::HGet),
-                    b"hset" => resp_arr.try_into().map(Command::HSet),
-                    b"hgetall" => resp_arr.try_into().map(Command::HGetAll),
-                    _ => Ok(Command::Unrecongnized(Unrecongnized(
-                        "unknown command".to_string(),
+                let parser = lookup(cmd.as_ref()).map(|(_, _, parse)| *parse);
+                let res = match parser {
+                    Some(parse) => parse(resp_arr),
+                    None => Ok(Command::Unrecognized(Unrecognized(
+                        String::from_utf8_lossy(cmd.as_ref()).into_owned(),
                     ))),
                 };
                 match res {
                     Ok(cmd) => Ok(cmd),
                     Err(e) => {
                         warn!("{}", e.to_string());
-                        Ok(Command::Unrecongnized(Unrecongnized(e.to_string())))
+                        Ok(Command::Invalid(Invalid(e)))
                     }
                 }
             }
@@ -79,25 +321,54 @@ impl TryFrom<RespFrame> for Command {
     }
 }
 
-impl CommandExecutor for Unrecongnized {
+impl CommandExecutor for Invalid {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RespFrame::SimpleError(SimpleError::new(format!("ERR {}", self.0)))
+    }
+}
+
+impl CommandExecutor for Unrecognized {
     fn execute(self, _: &Backend) -> RespFrame {
         // directly return an simple error
-        let msg = format!("Error unknown command: {:?}", self.0).to_string();
+        let msg = format!("ERR unknown command '{}'", self.0);
         RespFrame::SimpleError(SimpleError(msg))
     }
 }
 
+const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+// string commands refuse keys holding another type instead of shadowing them
+fn ensure_string(backend: &Backend, key: &str) -> Result<(), RespFrame> {
+    match backend.key_type(key) {
+        Some("string") | None => Ok(()),
+        Some(_) => Err(RespFrame::SimpleError(SimpleError::new(WRONGTYPE))),
+    }
+}
+
+// same as ensure_string for the hash commands
+fn ensure_hash(backend: &Backend, key: &str) -> Result<(), RespFrame> {
+    match backend.key_type(key) {
+        Some("hash") | None => Ok(()),
+        Some(_) => Err(RespFrame::SimpleError(SimpleError::new(WRONGTYPE))),
+    }
+}
+
 fn validate_command(
     value: &RespArray,
     names: &[&'static str],
     n_args: usize,
 ) -> Result<(), CommandError> {
-    if value.len() != n_args + names.len() {
-        return Err(CommandError::InvalidArgument(format!(
-            "{} command must have exactly {} arguments",
-            names.join(" "),
-            n_args
-        )));
+    validate_command_range(value, names, n_args..=n_args)
+}
+
+// same as validate_command for commands taking a variable number of arguments
+fn validate_command_range(
+    value: &RespArray,
+    names: &[&'static str],
+    n_args: impl RangeBounds<usize>,
+) -> Result<(), CommandError> {
+    if value.len() < names.len() || !n_args.contains(&(value.len() - names.len())) {
+        return Err(CommandError::WrongArity(names.join(" ")));
     };
 
     for (i, name) in names.iter().enumerate() {
@@ -122,23 +393,115 @@ fn validate_command(
     Ok(())
 }
 
+// parse a request made of the given parts and execute it, shared by the command tests
+#[cfg(test)]
+fn run<T>(backend: &Backend, parts: &[&[u8]]) -> anyhow::Result<RespFrame>
+where
+    T: TryFrom<RespArray> + CommandExecutor,
+    T::Error: std::error::Error + Send + Sync + 'static,
+{
+    Ok(T::try_from(RespArray::command(parts))?.execute(backend))
+}
+
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
-    Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
+    Ok(value.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         backend::Backend,
-        cmd::{Command, CommandExecutor},
-        RespArray, RespDecode, RespFrame, RespNull,
+        cmd::{
+            command_name, commands, is_write, lookup, replicated_write, validate_command,
+            validate_command_range, Command, CommandError, CommandExecutor,
+        },
+        BulkString, NullBulkString, RespArray, RespDecode, RespFrame, RespNull, SimpleError,
+        SimpleString,
     };
     use anyhow::Result;
     use bytes::BytesMut;
 
+    #[test]
+    fn test_registry_routes_commands() -> Result<()> {
+        let parse = |parts: &[&[u8]]| Command::try_from(RespFrame::from(RespArray::command(parts)));
+        assert!(matches!(parse(&[b"get", b"k"])?, Command::Get(_)));
+        assert!(matches!(parse(&[b"SET", b"k", b"v"])?, Command::Set(_)));
+        assert!(matches!(parse(&[b"HGet", b"k", b"f"])?, Command::HGet(_)));
+        assert!(matches!(parse(&[b"ping"])?, Command::Ping(_)));
+        assert!(matches!(parse(&[b"nope"])?, Command::Unrecognized(_)));
+        assert_eq!(
+            command_name(&RespArray::command(&[b"Get"]).into()),
+            Some("get")
+        );
+
+        // every table entry is reachable and names are unique
+        assert_eq!(super::REGISTRY.len(), commands().count());
+        for (name, _, _) in commands() {
+            assert_eq!(
+                lookup(name.as_bytes()).map(|(known, _, _)| *known),
+                Some(*name)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_write() {
+        assert!(is_write("set"));
+        assert!(is_write("restore"));
+        assert!(!is_write("get"));
+        assert!(!is_write("unknown"));
+    }
+
+    #[test]
+    fn test_replicated_write_sends_absolute_deadlines() -> Result<()> {
+        let backend = Backend::default();
+        let replicated = |parts: &[&[u8]]| {
+            let args = RespArray::command(parts);
+            Command::try_from(RespFrame::from(args.clone()))?.execute(&backend);
+            anyhow::Ok(replicated_write(&args, &backend))
+        };
+        let deadline = |key: &str| backend.expire_time(key).unwrap_or_default().to_string();
+
+        let write = replicated(&[b"setex", b"k", b"100", b"v"])?;
+        let expected = RespArray::command(&[b"set", b"k", b"v", b"pxat", deadline("k").as_bytes()]);
+        assert_eq!(write, expected);
+
+        for expire in [&b"expire"[..], b"pexpire"] {
+            let write = replicated(&[expire, b"k", b"100000"])?;
+            let expected = RespArray::command(&[b"pexpireat", b"k", deadline("k").as_bytes()]);
+            assert_eq!(write, expected);
+        }
+
+        let write = replicated(&[b"getex", b"k", b"px", b"5000"])?;
+        let expected = RespArray::command(&[b"pexpireat", b"k", deadline("k").as_bytes()]);
+        assert_eq!(write, expected);
+        let write = replicated(&[b"getex", b"k"])?;
+        assert_eq!(write, RespArray::command(&[b"getex", b"k"]));
+
+        let payload = Vec::<u8>::try_from(
+            Command::try_from(RespFrame::from(RespArray::command(&[b"dump", b"k"])))?
+                .execute(&backend),
+        )?;
+        let write = replicated(&[b"restore", b"r", b"100000", &payload])?;
+        let expected = RespArray::command(&[
+            b"restore",
+            b"r",
+            deadline("r").as_bytes(),
+            &payload,
+            b"absttl",
+        ]);
+        assert_eq!(write, expected);
+
+        // a persistent key is sent as is
+        let write = replicated(&[b"set", b"p", b"v"])?;
+        assert_eq!(write, RespArray::command(&[b"set", b"p", b"v"]));
+        Ok(())
+    }
+
     #[test]
     fn test_command() -> Result<()> {
-        let backend = Backend::new();
+        let backend = Backend::default();
 
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
@@ -148,8 +511,118 @@ mod tests {
         let cmd: Command = frame.try_into()?;
 
         let ret = cmd.execute(&backend);
-        assert_eq!(ret, RespFrame::Null(RespNull));
+        assert_eq!(ret, NullBulkString.into());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_into_string() -> Result<()> {
+        let s: String = RespFrame::from("hello").try_into()?;
+        assert_eq!(s, "hello");
+        let s: String = RespFrame::SimpleString(SimpleString::new("OK".to_string())).try_into()?;
+        assert_eq!(s, "OK");
+
+        let invalid = RespFrame::BulkString(BulkString::new(vec![0xff]));
+        let ret: Result<String, _> = invalid.try_into();
+        assert!(matches!(ret, Err(CommandError::FromUTF8Error(_))));
+        let ret: Result<String, _> = RespFrame::Integer(1).try_into();
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_into_bytes() -> Result<()> {
+        let v: Vec<u8> = RespFrame::BulkString(BulkString::new(vec![0xff, 0x00])).try_into()?;
+        assert_eq!(v, vec![0xff, 0x00]);
+        let ret: Result<Vec<u8>, _> = RespFrame::Null(RespNull).try_into();
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_into_i64() -> Result<()> {
+        assert_eq!(i64::try_from(&RespFrame::Integer(-5))?, -5);
+        assert_eq!(i64::try_from(&RespFrame::from("42"))?, 42);
+
+        let ret = i64::try_from(&RespFrame::from("4.2"));
+        assert!(matches!(ret, Err(CommandError::ParseIntError(_))));
+        let ret = i64::try_from(&RespFrame::Double(4.2));
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_into_f64() -> Result<()> {
+        assert_eq!(f64::try_from(&RespFrame::Double(1.5))?, 1.5);
+        assert_eq!(f64::try_from(&RespFrame::Integer(3))?, 3.0);
+        assert_eq!(f64::try_from(&RespFrame::from("-0.25"))?, -0.25);
+
+        let ret = f64::try_from(&RespFrame::from("abc"));
+        assert!(matches!(ret, Err(CommandError::ParseFloatError(_))));
+        let ret = f64::try_from(&RespFrame::Boolean(true));
+        assert!(matches!(ret, Err(CommandError::InvalidArgument(_))));
+        Ok(())
+    }
 
+    #[test]
+    fn test_validate_fixed_arity() {
+        let too_few = RespArray::command(&[b"get"]);
+        let err = validate_command(&too_few, &["get"], 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'get' command"
+        );
+        let too_many = RespArray::command(&[b"get", b"a", b"b"]);
+        assert!(matches!(
+            validate_command(&too_many, &["get"], 1),
+            Err(CommandError::WrongArity(_))
+        ));
+        let ok = RespArray::command(&[b"GET", b"a"]);
+        assert!(validate_command(&ok, &["get"], 1).is_ok());
+    }
+
+    #[test]
+    fn test_validate_variadic_arity() {
+        let too_few = RespArray::command(&[b"touch"]);
+        let err = validate_command_range(&too_few, &["touch"], 1..).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong number of arguments for 'touch' command"
+        );
+        let many = RespArray::command(&[b"touch", b"a", b"b", b"c", b"d"]);
+        assert!(validate_command_range(&many, &["touch"], 1..).is_ok());
+
+        let too_many = RespArray::command(&[b"copy", b"a", b"b", b"replace", b"x"]);
+        assert!(matches!(
+            validate_command_range(&too_many, &["copy"], 2..=3),
+            Err(CommandError::WrongArity(_))
+        ));
+        let empty = RespArray::new(vec![]);
+        assert!(validate_command_range(&empty, &["copy"], 2..=3).is_err());
+    }
+
+    #[test]
+    fn test_known_command_with_bad_arguments() -> Result<()> {
+        let mut buf = BytesMut::from(&b"*1\r\n$3\r\nset\r\n"[..]);
+        let frame = RespFrame::Array(RespArray::decode(&mut buf)?);
+        let cmd: Command = frame.try_into()?;
+        assert_eq!(
+            cmd.execute(&Backend::default()),
+            SimpleError::new("ERR wrong number of arguments for 'set' command").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_command_names_the_command() -> Result<()> {
+        let mut buf = BytesMut::from(&b"*1\r\n$6\r\nfoobar\r\n"[..]);
+        let frame = RespFrame::Array(RespArray::decode(&mut buf)?);
+        let cmd: Command = frame.try_into()?;
+        assert_eq!(
+            cmd.execute(&Backend::default()),
+            SimpleError::new("ERR unknown command 'foobar'").into()
+        );
         Ok(())
     }
 }
@@ -0,0 +1,285 @@
+use crate::{backend::Backend, BulkString, RespArray, RespFrame, SimpleError};
+
+use super::{
+    ensure_string, extract_args, validate_command, Command, CommandEntry, CommandError,
+    CommandExecutor,
+};
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("incr", true, |v| v.try_into().map(Command::Incr)),
+    ("decr", true, |v| v.try_into().map(Command::Decr)),
+    ("incrbyfloat", true, |v| {
+        v.try_into().map(Command::IncrByFloat)
+    }),
+];
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Incr {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Decr {
+    key: String,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct IncrByFloat {
+    key: String,
+    delta: f64,
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        match backend.incr_by(self.key, 1) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => RespFrame::SimpleError(SimpleError::new(e)),
+        }
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        match backend.incr_by(self.key, -1) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => RespFrame::SimpleError(SimpleError::new(e)),
+        }
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Err(e) = ensure_string(backend, &self.key) {
+            return e;
+        }
+        let ret = backend.update(self.key, |current| {
+            let current = match current {
+                Some(RespFrame::BulkString(v)) => std::str::from_utf8(v.as_ref())
+                    .ok()
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .ok_or("ERR value is not a valid float")?,
+                Some(RespFrame::Integer(v)) => *v as f64,
+                Some(RespFrame::Double(v)) => *v,
+                Some(_) => return Err("ERR value is not a valid float"),
+                None => 0.0,
+            };
+            let value = current + self.delta;
+            if !value.is_finite() {
+                return Err("ERR increment would produce NaN or Infinity");
+            }
+            // shortest representation that round-trips, so no trailing zeros
+            Ok(BulkString::new(value.to_string()).into())
+        });
+        match ret {
+            Ok(v) => v,
+            Err(e) => RespFrame::SimpleError(SimpleError::new(e)),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(key) => Ok(Incr {
+                key: key.try_into()?,
+            }),
+            None => Err(CommandError::InvalidArgument("Invalid Key".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(key) => Ok(Decr {
+                key: key.try_into()?,
+            }),
+            None => Err(CommandError::InvalidArgument("Invalid Key".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrbyfloat"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(key), Some(delta)) => Ok(IncrByFloat {
+                key: key.try_into()?,
+                delta: f64::try_from(&delta)?,
+            }),
+            _ => Err(CommandError::InvalidArgument("Invalid Arguments".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{Decr, Incr, IncrByFloat};
+    use crate::{
+        backend::Backend,
+        cmd::{run, CommandExecutor},
+        BulkString, RespArray, RespFrame, SimpleError,
+    };
+
+    #[test]
+    fn test_incrbyfloat_from_resp_array() -> Result<()> {
+        let cmd = IncrByFloat::try_from(RespArray::command(&[b"incrbyfloat", b"k", b"-1.5e2"]))?;
+        assert_eq!(cmd.key, "k");
+        assert_eq!(cmd.delta, -150.0);
+        assert!(IncrByFloat::try_from(RespArray::command(&[b"incrbyfloat", b"k", b"x"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_command() -> Result<()> {
+        let backend = Backend::default();
+        let incrbyfloat = |key: &str, delta: &[u8]| -> Result<_> {
+            let cmd = IncrByFloat::try_from(RespArray::command(&[
+                b"incrbyfloat",
+                key.as_bytes(),
+                delta,
+            ]))?;
+            Ok(cmd.execute(&backend))
+        };
+
+        // missing keys start at zero
+        assert_eq!(incrbyfloat("f", b"10.50")?, BulkString::new(b"10.5").into());
+        assert_eq!(incrbyfloat("f", b"0.1")?, BulkString::new(b"10.6").into());
+        assert_eq!(backend.get("f"), Some(BulkString::new(b"10.6").into()));
+
+        backend.set("g".to_string(), BulkString::new(b"5.0e3").into());
+        assert_eq!(incrbyfloat("g", b"2.0e2")?, BulkString::new(b"5200").into());
+
+        // the extra digits are below f64 precision
+        backend.set("h".to_string(), BulkString::new(b"3.0").into());
+        assert_eq!(
+            incrbyfloat("h", b"1.000000000000000005")?,
+            BulkString::new(b"4").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_errors() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("s".to_string(), BulkString::new(b"abc").into());
+        let cmd = IncrByFloat::try_from(RespArray::command(&[b"incrbyfloat", b"s", b"1"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR value is not a valid float").into()
+        );
+        assert_eq!(backend.get("s"), Some(BulkString::new(b"abc").into()));
+
+        backend.set("big".to_string(), BulkString::new(b"1.7e308").into());
+        let cmd = IncrByFloat::try_from(RespArray::command(&[b"incrbyfloat", b"big", b"1.7e308"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            SimpleError::new("ERR increment would produce NaN or Infinity").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_command() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(
+            run::<Incr>(&backend, &[b"incr", b"n"])?,
+            RespFrame::Integer(1)
+        );
+        backend.set("n".to_string(), BulkString::new(b"41").into());
+        assert_eq!(
+            run::<Incr>(&backend, &[b"incr", b"n"])?,
+            RespFrame::Integer(42)
+        );
+        assert_eq!(backend.get("n"), Some(BulkString::new(b"42").into()));
+
+        backend.set(
+            "max".to_string(),
+            BulkString::new(i64::MAX.to_string()).into(),
+        );
+        assert_eq!(
+            run::<Incr>(&backend, &[b"incr", b"max"])?,
+            SimpleError::new("ERR increment or decrement would overflow").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_wrongtype() -> Result<()> {
+        let backend = Backend::default();
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"1").into(),
+        );
+        let wrongtype: RespFrame =
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into();
+        assert_eq!(run::<Incr>(&backend, &[b"incr", b"h"])?, wrongtype);
+        assert_eq!(
+            run::<IncrByFloat>(&backend, &[b"incrbyfloat", b"h", b"1.5"])?,
+            wrongtype
+        );
+        // the hash is left alone instead of being shadowed by a string
+        assert_eq!(backend.get("h"), None);
+        assert_eq!(backend.hget("h", "f"), Some(BulkString::new(b"1").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_counter_reads_back_as_string() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("c".to_string(), BulkString::new(b"10").into());
+        assert_eq!(
+            run::<Incr>(&backend, &[b"incr", b"c"])?,
+            RespFrame::Integer(11)
+        );
+        // stored natively from now on, but GET still sees the decimal string
+        assert_eq!(
+            backend.map.get("c").map(|v| v.clone()),
+            Some(RespFrame::Integer(11))
+        );
+        assert_eq!(backend.get("c"), Some(BulkString::new(b"11").into()));
+
+        assert_eq!(
+            run::<Decr>(&backend, &[b"decr", b"c"])?,
+            RespFrame::Integer(10)
+        );
+        assert_eq!(
+            run::<Decr>(&backend, &[b"decr", b"missing"])?,
+            RespFrame::Integer(-1)
+        );
+        backend.set(
+            "min".to_string(),
+            BulkString::new(i64::MIN.to_string()).into(),
+        );
+        assert_eq!(
+            run::<Decr>(&backend, &[b"decr", b"min"])?,
+            SimpleError::new("ERR increment or decrement would overflow").into()
+        );
+        Ok(())
+    }
+}
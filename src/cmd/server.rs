@@ -0,0 +1,830 @@
+use std::{fs::OpenOptions, io::Write as _, path::Path, time::Duration};
+
+use bytes::BytesMut;
+
+use crate::{
+    backend::Backend, network, BulkString, RespArray, RespDecode, RespEncode, RespFrame,
+    SimpleError, SimpleString,
+};
+
+use super::{
+    commands, extract_args,
+    keyspace::{restore_commands, snapshot_from_restore_commands},
+    validate_command, validate_command_range, Command, CommandEntry, CommandError, CommandExecutor,
+    ConnectionContext, RESP_OK,
+};
+
+// DEBUG subcommands sent by client test suites that are safe to acknowledge without doing anything
+const DEBUG_NOOPS: &[&str] = &[
+    "quicklist-packed-threshold",
+    "stringmatch-len",
+    "set-skip-checksum-validation",
+    "replybuffer",
+    "jmap",
+];
+
+// registered in the dispatch table by the parent module
+pub(super) const COMMANDS: &[CommandEntry] = &[
+    ("wait", false, |v| v.try_into().map(Command::Wait)),
+    ("monitor", false, |v| v.try_into().map(Command::Monitor)),
+    ("reset", false, |v| v.try_into().map(Command::Reset)),
+    ("debug", false, |v| v.try_into().map(Command::Debug)),
+    ("command", false, |v| v.try_into().map(Command::CommandInfo)),
+    ("info", false, |v| v.try_into().map(Command::Info)),
+    ("slowlog", false, |v| v.try_into().map(Command::SlowLog)),
+    ("replicaof", false, |v| v.try_into().map(Command::ReplicaOf)),
+    ("sync", false, |v| v.try_into().map(Command::FullSync)),
+];
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Wait {
+    numreplicas: i64,
+    timeout: i64,
+}
+
+/// The connection handler switches the client to streaming mode after the reply.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Monitor;
+
+/// Restores the connection defaults, the handler uses it to leave MONITOR mode.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Reset;
+
+/// Introspection of the supported commands.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum CommandInfo {
+    List,
+    Count,
+}
+
+/// Server report, only the commandstats section is tracked.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct Info {
+    section: Option<String>,
+}
+
+/// Follow the primary at the given address, or stop replicating with `NO ONE`.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum ReplicaOf {
+    NoOne,
+    Primary(String),
+}
+
+/// Sent by a replica, the connection handler then streams the data set and every write.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct FullSync;
+
+/// Commands that ran longer than the slowlog threshold.
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum SlowLog {
+    /// newest first, at most this many entries
+    Get(usize),
+    Len,
+    Reset,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Debug {
+    /// only awaited by `execute_async`, replies right away otherwise
+    Sleep(Duration),
+    Object(String),
+    /// toggles the background expiry cycle, keys then only expire when accessed
+    SetActiveExpire(bool),
+    /// round trips the dataset through a temporary file
+    Reload,
+    /// tuning knobs of redis internals this server does not have, accepted and ignored
+    Noop,
+    Unsupported(String),
+}
+
+impl CommandExecutor for Wait {
+    fn execute(self, _: &Backend) -> RespFrame {
+        if self.timeout < 0 {
+            return RespFrame::SimpleError(SimpleError::new("ERR timeout is negative"));
+        }
+        // deliberately not implemented, replicas never acknowledge offsets so none is counted
+        RespFrame::Integer(0)
+    }
+}
+
+impl CommandExecutor for Monitor {
+    fn execute(self, _: &Backend) -> RespFrame {
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Reset {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleString::new("RESET".to_string()).into()
+    }
+
+    // like redis the connection goes back to RESP2
+    async fn execute_async(self, backend: &Backend, context: &mut ConnectionContext) -> RespFrame {
+        context.protocol = 2;
+        self.execute(backend)
+    }
+}
+
+impl CommandExecutor for SlowLog {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            SlowLog::Get(count) => {
+                let entries = backend
+                    .slowlog(count)
+                    .into_iter()
+                    .map(|entry| {
+                        RespArray::new(vec![
+                            RespFrame::Integer(entry.id as i64),
+                            RespFrame::Integer(entry.timestamp as i64),
+                            RespFrame::Integer(entry.duration.as_micros() as i64),
+                            entry.args.into(),
+                        ])
+                        .into()
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(entries).into()
+            }
+            SlowLog::Len => RespFrame::Integer(backend.slowlog_len() as i64),
+            SlowLog::Reset => {
+                backend.reset_slowlog();
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+impl CommandExecutor for CommandInfo {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self {
+            CommandInfo::List => RespArray::new(
+                commands()
+                    .map(|(name, _, _)| BulkString::new(*name).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            CommandInfo::Count => RespFrame::Integer(commands().count() as i64),
+        }
+    }
+}
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // unknown sections yield an empty report like redis
+        let mut report = String::new();
+        if matches!(
+            self.section.as_deref(),
+            None | Some("commandstats" | "all" | "everything" | "default")
+        ) {
+            report.push_str("# Commandstats\r\n");
+            for (name, stats) in backend.command_stats() {
+                report.push_str(&format!(
+                    "cmdstat_{}:calls={},usec={},usec_per_call={:.2}\r\n",
+                    name,
+                    stats.calls,
+                    stats.usec,
+                    stats.usec_per_call()
+                ));
+            }
+        }
+        BulkString::new(report).into()
+    }
+}
+
+impl CommandExecutor for ReplicaOf {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            ReplicaOf::NoOne => backend.unfollow(),
+            ReplicaOf::Primary(addr) => {
+                let Ok(handle) = tokio::runtime::Handle::try_current() else {
+                    return RespFrame::SimpleError(SimpleError::new(
+                        "ERR replication needs a running server",
+                    ));
+                };
+                backend.follow(|| {
+                    handle
+                        .spawn(network::replicate(addr, backend.clone()))
+                        .abort_handle()
+                });
+            }
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for FullSync {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleString::new("FULLRESYNC".to_string()).into()
+    }
+}
+
+impl CommandExecutor for Debug {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self {
+            Debug::Sleep(_) => RESP_OK.clone(),
+            Debug::Object(key) => debug_object(backend, &key),
+            Debug::SetActiveExpire(enabled) => {
+                backend.set_active_expire(enabled);
+                RESP_OK.clone()
+            }
+            Debug::Reload => reload_reply(reload(backend)),
+            Debug::Noop => RESP_OK.clone(),
+            Debug::Unsupported(subcommand) => RespFrame::SimpleError(SimpleError::new(format!(
+                "ERR DEBUG subcommand not supported: {}",
+                subcommand
+            ))),
+        }
+    }
+
+    // sleeping must not block the executor, so it only happens on the async path
+    async fn execute_async(self, backend: &Backend, _: &mut ConnectionContext) -> RespFrame {
+        match self {
+            Debug::Sleep(duration) => {
+                tokio::time::sleep(duration).await;
+                RESP_OK.clone()
+            }
+            // the dump goes through a file, which must not block the executor either
+            Debug::Reload => {
+                let backend = backend.clone();
+                let reloaded = tokio::task::spawn_blocking(move || reload(&backend)).await;
+                reload_reply(reloaded.unwrap_or_else(|e| Err(e.into())))
+            }
+            debug => backend.execute_read(|| debug.execute(backend)),
+        }
+    }
+}
+
+// what SAVE followed by a restart would do, minus the restart
+fn reload(backend: &Backend) -> anyhow::Result<()> {
+    backend.rebuild(|snapshot| {
+        let dump = restore_commands(backend, snapshot)
+            .into_iter()
+            .flat_map(|command| command.encode())
+            .collect::<Vec<u8>>();
+        let path = std::env::temp_dir().join(format!(
+            "simple-redis-{}-{:016x}.reload",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        let read = write_dump(&path, &dump).and_then(|_| std::fs::read(&path));
+        let _ = std::fs::remove_file(&path);
+
+        let mut buf = BytesMut::from(read?.as_slice());
+        let mut commands = vec![];
+        while !buf.is_empty() {
+            commands.push(RespArray::decode(&mut buf)?);
+        }
+        Ok(snapshot_from_restore_commands(backend, commands)?)
+    })
+}
+
+// the temp dir is shared, so never follow a planted file or symlink and keep the data private
+fn write_dump(path: &Path, dump: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(dump)
+}
+
+fn reload_reply(reloaded: anyhow::Result<()>) -> RespFrame {
+    match reloaded {
+        Ok(()) => RESP_OK.clone(),
+        Err(e) => RespFrame::SimpleError(SimpleError::new(format!(
+            "ERR Error trying to load the RDB dump: {}",
+            e
+        ))),
+    }
+}
+
+// values have no stable address or shared refcount here, so those fields are fixed
+fn debug_object(backend: &Backend, key: &str) -> RespFrame {
+    // none of these count as an access, so the idle time is the one before the command
+    let (Some(idle), Some(encoding), Some(value)) = (
+        backend.idle_time(key),
+        backend.encoding(key),
+        backend.peek(key),
+    ) else {
+        return RespFrame::SimpleError(SimpleError::new("ERR no such key"));
+    };
+    let serialized = value.encode();
+    SimpleString::new(format!(
+        "refcount:1 encoding:{} serializedlength:{} lru_seconds_idle:{}",
+        encoding,
+        serialized.len(),
+        idle.as_secs()
+    ))
+    .into()
+}
+
+impl TryFrom<RespArray> for Wait {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["wait"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(numreplicas)), Some(RespFrame::BulkString(timeout))) => {
+                Ok(Wait {
+                    numreplicas: String::from_utf8(numreplicas.0.into())?.parse()?,
+                    timeout: String::from_utf8(timeout.0.into())?.parse()?,
+                })
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid numreplicas or timeout".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Monitor {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["monitor"], 0)?;
+        Ok(Monitor)
+    }
+}
+
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["reset"], 0)?;
+        Ok(Reset)
+    }
+}
+
+impl TryFrom<RespArray> for SlowLog {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["slowlog"], 1..=2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand: String = match args.next() {
+            Some(subcommand) => subcommand.try_into()?,
+            None => return Err(CommandError::WrongArity("slowlog".to_string())),
+        };
+        match (subcommand.to_ascii_lowercase().as_str(), args.next()) {
+            // redis returns the 10 newest entries by default, a negative count means all
+            ("get", None) => Ok(SlowLog::Get(10)),
+            ("get", Some(count)) => {
+                let count = i64::try_from(&count)?;
+                Ok(SlowLog::Get(usize::try_from(count).unwrap_or(usize::MAX)))
+            }
+            ("len", None) => Ok(SlowLog::Len),
+            ("reset", None) => Ok(SlowLog::Reset),
+            ("len" | "reset", _) => {
+                Err(CommandError::WrongArity(format!("slowlog|{}", subcommand)))
+            }
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                subcommand
+            ))),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for CommandInfo {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["command"], 0..=1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            None => Ok(CommandInfo::List),
+            Some(RespFrame::BulkString(subcommand)) => {
+                match subcommand.as_ref().to_ascii_lowercase().as_slice() {
+                    b"list" | b"docs" => Ok(CommandInfo::List),
+                    b"count" => Ok(CommandInfo::Count),
+                    _ => Err(CommandError::InvalidArgument(format!(
+                        "unknown subcommand '{}'",
+                        String::from_utf8_lossy(subcommand.as_ref())
+                    ))),
+                }
+            }
+            Some(_) => Err(CommandError::InvalidArgument(
+                "Invalid subcommand".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["info"], 0..=1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            None => Ok(Info { section: None }),
+            Some(RespFrame::BulkString(section)) => Ok(Info {
+                section: Some(String::from_utf8(section.0.into())?.to_ascii_lowercase()),
+            }),
+            Some(_) => Err(CommandError::InvalidArgument("Invalid section".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ReplicaOf {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["replicaof"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(host)), Some(RespFrame::BulkString(port))) => {
+                let host = String::from_utf8(host.0.into())?;
+                let port = String::from_utf8(port.0.into())?;
+                if host.eq_ignore_ascii_case("no") && port.eq_ignore_ascii_case("one") {
+                    return Ok(ReplicaOf::NoOne);
+                }
+                let port = port
+                    .parse::<u16>()
+                    .map_err(|_| CommandError::InvalidArgument("Invalid master port".into()))?;
+                Ok(ReplicaOf::Primary(format!("{}:{}", host, port)))
+            }
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid host or port".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for FullSync {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["sync"], 0)?;
+        Ok(FullSync)
+    }
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command_range(&value, &["debug"], 1..)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let subcommand: String = match args.next() {
+            Some(subcommand) => subcommand.try_into()?,
+            None => return Err(CommandError::WrongArity("debug".to_string())),
+        };
+        let name = subcommand.to_ascii_lowercase();
+        if DEBUG_NOOPS.contains(&name.as_str()) {
+            return Ok(Debug::Noop);
+        }
+        let arg = match (name.as_str(), args.next(), args.next()) {
+            ("reload", None, _) => return Ok(Debug::Reload),
+            ("reload", Some(_), _) => {
+                return Err(CommandError::WrongArity("debug|reload".to_string()))
+            }
+            ("object" | "sleep" | "set-active-expire", Some(arg), None) => arg,
+            ("object" | "sleep" | "set-active-expire", _, _) => {
+                return Err(CommandError::WrongArity(format!("debug|{}", name)))
+            }
+            _ => return Ok(Debug::Unsupported(subcommand)),
+        };
+        match name.as_str() {
+            "object" => Ok(Debug::Object(arg.try_into()?)),
+            "sleep" => {
+                let seconds = f64::try_from(&arg)?;
+                Duration::try_from_secs_f64(seconds)
+                    .map(Debug::Sleep)
+                    .map_err(|_| {
+                        CommandError::InvalidArgument("invalid sleep duration".to_string())
+                    })
+            }
+            _ => match i64::try_from(&arg) {
+                Ok(0) => Ok(Debug::SetActiveExpire(false)),
+                Ok(1) => Ok(Debug::SetActiveExpire(true)),
+                _ => Err(CommandError::InvalidArgument(
+                    "set-active-expire expects 0 or 1".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use anyhow::Result;
+
+    use crate::{
+        backend::Backend,
+        cmd::{keyspace::DbSize, run, Command, CommandExecutor, ConnectionContext, RESP_OK},
+        BulkString, RespArray, RespFrame, SimpleError,
+    };
+
+    use super::{CommandInfo, Debug, Info, ReplicaOf, Wait};
+
+    #[test]
+    fn test_wait_command() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"wait", b"0", b"100"]);
+        let cmd = Wait::try_from(resp_arr)?;
+        assert_eq!(cmd.numreplicas, 0);
+        assert_eq!(cmd.timeout, 100);
+        assert_eq!(cmd.execute(&Backend::default()), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_malformed_argument() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"wait", b"zero", b"100"]);
+        assert!(Wait::try_from(resp_arr.clone()).is_err());
+
+        let cmd: Command = RespFrame::Array(resp_arr).try_into()?;
+        assert!(matches!(
+            cmd.execute(&Backend::default()),
+            RespFrame::SimpleError(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_sleep_from_resp_array() -> Result<()> {
+        let resp_arr = RespArray::command(&[b"debug", b"SLEEP", b"0.5"]);
+        let cmd = Debug::try_from(resp_arr)?;
+        assert_eq!(cmd, Debug::Sleep(Duration::from_millis(500)));
+
+        let resp_arr = RespArray::command(&[b"debug", b"sleep", b"-1"]);
+        assert!(Debug::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_object() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("foo".to_string(), BulkString::new(b"hello").into());
+
+        let cmd = Debug::try_from(RespArray::command(&[b"debug", b"OBJECT", b"foo"]))?;
+        assert_eq!(cmd, Debug::Object("foo".to_string()));
+        let RespFrame::SimpleString(details) = cmd.execute(&backend) else {
+            panic!("debug object must reply a simple string");
+        };
+        assert!(details.contains("encoding:embstr"));
+        // $5\r\nhello\r\n
+        assert!(details.contains("serializedlength:11"));
+
+        let cmd = Debug::Object("missing".to_string());
+        assert!(matches!(cmd.execute(&backend), RespFrame::SimpleError(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_object_does_not_touch_the_key() -> Result<()> {
+        let backend = Backend::default();
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        let idle = backend.idle_time("h").expect("the key exists");
+
+        let RespFrame::SimpleString(details) = Debug::Object("h".to_string()).execute(&backend)
+        else {
+            panic!("debug object must reply a simple string");
+        };
+        assert!(details.contains("encoding:hashtable"));
+        // %1\r\n+f\r\n$1\r\nv\r\n
+        assert!(details.contains("serializedlength:15"));
+        assert!(backend.idle_time("h").is_some_and(|after| after >= idle));
+        Ok(())
+    }
+
+    #[test]
+    fn test_replicaof_from_resp_array() -> Result<()> {
+        let cmd = ReplicaOf::try_from(RespArray::command(&[b"replicaof", b"NO", b"one"]))?;
+        assert_eq!(cmd, ReplicaOf::NoOne);
+
+        let cmd = ReplicaOf::try_from(RespArray::command(&[b"replicaof", b"10.0.0.1", b"6380"]))?;
+        assert_eq!(cmd, ReplicaOf::Primary("10.0.0.1:6380".to_string()));
+
+        let resp_arr = RespArray::command(&[b"replicaof", b"10.0.0.1", b"port"]);
+        assert!(ReplicaOf::try_from(resp_arr).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replica_becomes_primary_when_the_stream_ends() -> Result<()> {
+        let backend = Backend::default();
+        // nothing listens on the port once the listener is dropped
+        let port = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await?
+            .local_addr()?
+            .port()
+            .to_string();
+        assert_eq!(
+            run::<ReplicaOf>(&backend, &[b"replicaof", b"127.0.0.1", port.as_bytes()])?,
+            RESP_OK.clone()
+        );
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while backend.is_replica() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "still a replica of a primary it never reached"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_list() -> Result<()> {
+        let cmd: Command =
+            RespFrame::Array(RespArray::command(&[b"command", b"list"])).try_into()?;
+        let RespFrame::Array(names) = cmd.execute(&Backend::default()) else {
+            panic!("command list must reply an array");
+        };
+        for name in ["get", "set", "hget", "hset", "scan", "command"] {
+            assert!(
+                names.contains(&BulkString::new(name).into()),
+                "{} is missing",
+                name
+            );
+        }
+
+        let count = CommandInfo::try_from(RespArray::command(&[b"command", b"COUNT"]))?;
+        assert_eq!(
+            count.execute(&Backend::default()),
+            RespFrame::Integer(names.len() as i64)
+        );
+        assert!(CommandInfo::try_from(RespArray::command(&[b"command", b"nope"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_commandstats() -> Result<()> {
+        let backend = Backend::default();
+        backend.record_command("get", Duration::from_micros(30));
+        backend.record_command("get", Duration::from_micros(10));
+
+        let cmd = Info::try_from(RespArray::command(&[b"info", b"CommandStats"]))?;
+        assert_eq!(
+            cmd.execute(&backend),
+            BulkString::new(
+                "# Commandstats\r\ncmdstat_get:calls=2,usec=40,usec_per_call=20.00\r\n"
+            )
+            .into()
+        );
+
+        let cmd = Info::try_from(RespArray::command(&[b"info", b"replication"]))?;
+        assert_eq!(cmd.execute(&backend), BulkString::new("").into());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_active_expire_leaves_keys_until_accessed() -> Result<()> {
+        let backend = Backend::default();
+        let handle = backend.start_expiry_cycle(Duration::from_millis(5));
+        assert_eq!(
+            run::<Debug>(&backend, &[b"debug", b"set-active-expire", b"0"])?,
+            RESP_OK.clone()
+        );
+        backend.set("k".to_string(), BulkString::new(b"v").into());
+        backend.expire("k", Instant::now() + Duration::from_millis(5));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // the deadline passed but nothing reclaimed the key yet
+        assert_eq!(
+            run::<DbSize>(&backend, &[b"dbsize"])?,
+            RespFrame::Integer(1)
+        );
+        assert_eq!(backend.get("k"), None);
+        assert_eq!(
+            run::<DbSize>(&backend, &[b"dbsize"])?,
+            RespFrame::Integer(0)
+        );
+
+        assert!(
+            Debug::try_from(RespArray::command(&[b"debug", b"set-active-expire", b"2"])).is_err()
+        );
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_sleep_awaits_on_async_path() -> Result<()> {
+        let backend = Backend::default();
+        let mut context = ConnectionContext::default();
+        let cmd: Command =
+            RespFrame::Array(RespArray::command(&[b"debug", b"sleep", b"0.05"])).try_into()?;
+
+        let start = Instant::now();
+        let ret = cmd.execute_async(&backend, &mut context).await;
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        // commands without an async override run their sync path
+        let cmd: Command = RespFrame::Array(RespArray::command(&[b"dbsize"])).try_into()?;
+        assert_eq!(
+            cmd.execute_async(&backend, &mut context).await,
+            RespFrame::Integer(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_subcommand_dispatch() -> Result<()> {
+        let backend = Backend::default();
+        assert_eq!(
+            run::<Debug>(&backend, &[b"debug", b"QUICKLIST-PACKED-THRESHOLD", b"100"])?,
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            run::<Debug>(&backend, &[b"debug", b"populate", b"10"])?,
+            RespFrame::SimpleError(SimpleError::new(
+                "ERR DEBUG subcommand not supported: populate"
+            ))
+        );
+        assert!(Debug::try_from(RespArray::command(&[b"debug", b"sleep"])).is_err());
+        assert!(Debug::try_from(RespArray::command(&[b"debug"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_reload_keeps_values() -> Result<()> {
+        let backend = Backend::default();
+        backend.set("s".to_string(), BulkString::new(b"\x00binary\r\n").into());
+        backend.set("n".to_string(), BulkString::new(b"10").into());
+        backend
+            .incr_by("n".to_string(), 1)
+            .map_err(anyhow::Error::msg)?;
+        backend.hset(
+            "h".to_string(),
+            "f".to_string(),
+            BulkString::new(b"v").into(),
+        );
+        backend.expire("s", Instant::now() + Duration::from_secs(100));
+        let before = backend.snapshot();
+
+        assert_eq!(
+            run::<Debug>(&backend, &[b"debug", b"RELOAD"])?,
+            RESP_OK.clone()
+        );
+
+        let after = backend.snapshot();
+        assert_eq!(after.strings, before.strings);
+        assert_eq!(after.hashes, before.hashes);
+        assert!(backend
+            .ttl("s")
+            .is_some_and(|ttl| ttl > Duration::from_secs(99)));
+        assert_eq!(backend.ttl("n"), None);
+        assert!(Debug::try_from(RespArray::command(&[b"debug", b"reload", b"nosave"])).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_readers_never_see_a_reload_half_done() -> Result<()> {
+        const KEYS: i64 = 2_000;
+
+        let backend = Backend::default();
+        for i in 0..KEYS {
+            backend.set(format!("k{}", i), BulkString::new(b"v").into());
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = tokio::spawn({
+            let (backend, done) = (backend.clone(), done.clone());
+            async move {
+                let mut context = ConnectionContext::default();
+                while !done.load(Ordering::Relaxed) {
+                    let cmd = Command::try_from(RespFrame::from(RespArray::command(&[b"dbsize"])))?;
+                    assert_eq!(
+                        cmd.execute_async(&backend, &mut context).await,
+                        RespFrame::Integer(KEYS)
+                    );
+                    tokio::task::yield_now().await;
+                }
+                anyhow::Ok(())
+            }
+        });
+        let mut context = ConnectionContext::default();
+        for _ in 0..5 {
+            let cmd =
+                Command::try_from(RespFrame::from(RespArray::command(&[b"debug", b"reload"])))?;
+            assert_eq!(
+                cmd.execute_async(&backend, &mut context).await,
+                RESP_OK.clone()
+            );
+        }
+        done.store(true, Ordering::Relaxed);
+        reader.await??;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+/// Redis style glob matching used by pattern based commands:
+/// `*` any sequence, `?` any byte, `[abc]`/`[^a]`/`[a-z]` classes and `\` escapes.
+///
+/// Runs in `O(pattern * text)` without recursion: on a mismatch only the most recent `*`
+/// is retried one byte further, since an earlier star can never need to absorb more.
+pub(crate) fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // pattern position after the last star, and the text position it is retried from
+    let mut star = None;
+    while t < text.len() {
+        if pattern.get(p) == Some(&b'*') {
+            p += 1;
+            star = Some((p, t));
+            continue;
+        }
+        if let Some(next) = match_one(pattern, p, text[t]) {
+            p = next;
+            t += 1;
+            continue;
+        }
+        match star {
+            Some((after_star, from)) => {
+                p = after_star;
+                t = from + 1;
+                star = Some((after_star, t));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+// match one byte against the token at `p`, returning the position of the next token
+fn match_one(pattern: &[u8], p: usize, c: u8) -> Option<usize> {
+    match pattern.get(p)? {
+        b'?' => Some(p + 1),
+        b'[' => match match_class(&pattern[p + 1..], c)? {
+            (true, rest) => Some(pattern.len() - rest.len()),
+            (false, _) => None,
+        },
+        b'\\' if p + 1 < pattern.len() => (pattern[p + 1] == c).then_some(p + 2),
+        &b => (b == c).then_some(p + 1),
+    }
+}
+
+// match one byte against a `[...]` class, returning the result and the pattern after `]`
+fn match_class(pattern: &[u8], c: u8) -> Option<(bool, &[u8])> {
+    let (negate, mut i) = match pattern.first() {
+        Some(b'^') => (true, 1),
+        _ => (false, 0),
+    };
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            matched |= pattern[i + 1] == c;
+            i += 2;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            let (lo, hi) = if pattern[i] <= pattern[i + 2] {
+                (pattern[i], pattern[i + 2])
+            } else {
+                (pattern[i + 2], pattern[i])
+            };
+            matched |= lo <= c && c <= hi;
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    if i >= pattern.len() {
+        // unterminated class
+        return None;
+    }
+    Some((matched != negate, &pattern[i + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*", b"anything"));
+        assert!(glob_match(b"h?llo", b"hello"));
+        assert!(glob_match(b"h*llo", b"heeeello"));
+        assert!(glob_match(b"h[ae]llo", b"hallo"));
+        assert!(!glob_match(b"h[ae]llo", b"hillo"));
+        assert!(glob_match(b"h[^e]llo", b"hallo"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-b]llo", b"hbllo"));
+        assert!(glob_match(b"news.*", b"news.tech"));
+        assert!(!glob_match(b"news.*", b"new.tech"));
+        assert!(glob_match(b"a\\*b", b"a*b"));
+        assert!(!glob_match(b"a\\*b", b"axb"));
+        assert!(!glob_match(b"key", b"key1"));
+        assert!(glob_match(b"*a*b", b"xaxxab"));
+        assert!(!glob_match(b"*a*b", b"xaxxa"));
+        assert!(glob_match(b"a**", b"a"));
+        assert!(!glob_match(b"h[ae", b"ha"));
+    }
+
+    #[test]
+    fn test_glob_match_pathological_patterns() {
+        // exponential for a matcher retrying every split of every star
+        let text = vec![b'a'; 10_000];
+        assert!(!glob_match(b"*a*a*a*a*a*a*a*a*a*a*b", &text));
+        // one stack frame per pattern byte would overflow the stack
+        let pattern = vec![b'a'; 1_000_000];
+        assert!(!glob_match(&pattern, &text));
+        assert!(glob_match(
+            &[b"*".repeat(100_000), b"a".to_vec()].concat(),
+            &text
+        ));
+    }
+}
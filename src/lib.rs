@@ -1,7 +1,17 @@
 mod backend;
+#[cfg(feature = "client")]
+mod client;
 mod cmd;
+mod glob;
 pub mod network;
 mod resp;
+#[cfg(feature = "tls")]
+pub mod tls;
 
-pub use backend::Backend;
+pub use backend::{
+    Backend, BackendConfig, BackendSnapshot, ClientInfo, ClientRegistration, CommandStats,
+    EvictionConfig, EvictionPolicy, KeyEvent, KeyOp, SlowLogEntry,
+};
+#[cfg(feature = "client")]
+pub use client::Client;
 pub use resp::*;
@@ -1,26 +1,144 @@
-use anyhow::Result;
-use simple_redis::{network, Backend};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use simple_redis::{
+    network::{self, ConnectionConfig, ServerConfig},
+    Backend, BackendConfig, EvictionConfig, EvictionPolicy,
+};
 use tokio::net::TcpListener;
 use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
+    // RUST_LOG=warn silences the per request logs, info is the default
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let backend_config = backend_config();
+    // a password that is not enforced would leave the server open while looking protected
+    if backend_config.requirepass.is_some() {
+        bail!("REDIS_REQUIREPASS is set but AUTH is not supported, refusing to start");
+    }
+    // only database 0 exists, a different count would be silently ignored
+    if backend_config.databases != BackendConfig::default().databases {
+        bail!("REDIS_DATABASES is set but SELECT is not supported, refusing to start");
+    }
+    if backend_config.appendonly {
+        warn!(
+            "REDIS_APPENDONLY is set but persistence is not supported, data lives in memory only"
+        );
+    }
 
     let addr = "0.0.0.0:6379";
-    info!("Mini Redis is listening on {}", addr);
     let listener = TcpListener::bind(addr).await?;
+    info!("Mini Redis is listening on {}", addr);
 
-    let backend = Backend::new();
-    loop {
-        let (stream, peer_addr) = listener.accept().await?;
-        info!("Accepted connection from {}", peer_addr);
-        let cloned_backend = backend.clone();
+    let backend = Backend::new(backend_config);
+    let mut config = ServerConfig {
+        connection: ConnectionConfig {
+            idle_timeout: backend.config().timeout,
+            write_timeout: env_duration("REDIS_WRITE_TIMEOUT"),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    if let Some(max_clients) = env_parse("REDIS_MAXCLIENTS") {
+        config.max_clients = max_clients;
+    }
+    // REDIS_TCP_NODELAY=false lets the kernel coalesce small replies
+    if let Some(nodelay) = env_parse("REDIS_TCP_NODELAY") {
+        config.tcp_nodelay = nodelay;
+    }
+    // keepalive probes start after REDIS_TCP_KEEPALIVE idle seconds, 0 disables them
+    if let Some(secs) = env_parse::<u64>("REDIS_TCP_KEEPALIVE") {
+        config.tcp_keepalive = (secs > 0).then(|| Duration::from_secs(secs));
+    }
+    // commands slower than REDIS_SLOWLOG_SLOWER_THAN microseconds are logged, negative disables
+    if let Some(usec) = env_parse::<i64>("REDIS_SLOWLOG_SLOWER_THAN") {
+        let threshold = u64::try_from(usec).ok().map(Duration::from_micros);
+        backend.set_slowlog_threshold(threshold);
+    }
+    backend.start_expiry_cycle(Duration::from_millis(100));
+
+    // local clients may additionally connect through a unix domain socket
+    #[cfg(unix)]
+    if let Ok(path) = std::env::var("REDIS_UNIX_SOCKET") {
+        let _ = std::fs::remove_file(&path);
+        let unix_listener = tokio::net::UnixListener::bind(&path)?;
+        info!("Mini Redis is listening on unix socket {}", path);
+        let backend = backend.clone();
         tokio::spawn(async move {
-            match network::stream_handler(stream, cloned_backend).await {
-                Ok(_) => info!("Connection from {} closed", peer_addr),
-                Err(e) => warn!("Error {} occurs while handle {} connection", e, peer_addr),
+            if let Err(e) = network::serve_unix(unix_listener, backend, config).await {
+                warn!("Unix socket listener stopped: {}", e);
             }
         });
     }
+
+    #[cfg(feature = "tls")]
+    if let (Ok(cert), Ok(key)) = (
+        std::env::var("REDIS_TLS_CERT"),
+        std::env::var("REDIS_TLS_KEY"),
+    ) {
+        info!("TLS enabled with certificate {}", cert);
+        let acceptor = simple_redis::tls::load_acceptor(cert, key)?;
+        mark_ready()?;
+        return network::serve_tls(listener, backend, config, acceptor).await;
+    }
+    mark_ready()?;
+    network::serve(listener, backend, config).await
+}
+
+fn backend_config() -> BackendConfig {
+    let default = BackendConfig::default();
+    BackendConfig {
+        // REDIS_MAXKEYS caps the keyspace, keys are evicted per REDIS_MAXKEYS_POLICY
+        eviction: env_parse::<usize>("REDIS_MAXKEYS")
+            .filter(|n| *n > 0)
+            .map(|max_keys| EvictionConfig {
+                max_keys,
+                policy: env_parse::<EvictionPolicy>("REDIS_MAXKEYS_POLICY").unwrap_or_default(),
+            }),
+        databases: env_parse("REDIS_DATABASES").unwrap_or(default.databases),
+        requirepass: std::env::var("REDIS_REQUIREPASS").ok(),
+        timeout: env_duration("REDIS_TIMEOUT"),
+        appendonly: env_parse("REDIS_APPENDONLY").unwrap_or(default.appendonly),
+        // REDIS_SHARDS and REDIS_INITIAL_CAPACITY tune the keyspace maps for many concurrent clients
+        shards: match (
+            env_parse::<usize>("REDIS_SHARDS"),
+            env_parse::<usize>("REDIS_INITIAL_CAPACITY"),
+        ) {
+            (None, None) => None,
+            (shards, _) => Some(shards.unwrap_or_else(default_shards)),
+        },
+        initial_capacity: env_parse("REDIS_INITIAL_CAPACITY").unwrap_or_default(),
+    }
+}
+
+// orchestrators can wait for REDIS_READY_FILE to appear instead of polling with PING
+fn mark_ready() -> Result<()> {
+    info!("Ready to accept connections");
+    if let Ok(path) = std::env::var("REDIS_READY_FILE") {
+        std::fs::write(path, std::process::id().to_string())?;
+    }
+    Ok(())
+}
+
+// same default as dashmap, four shards per core
+fn default_shards() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from) * 4
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.parse::<T>().ok())
+}
+
+// timeouts are given in seconds, unset or 0 disables them
+fn env_duration(name: &str) -> Option<Duration> {
+    env_parse::<u64>(name)
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
 }
@@ -1,15 +1,37 @@
-use anyhow::Result;
+use std::{
+    future::Future,
+    io,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
 use futures::SinkExt as _;
-use tokio::net::TcpStream;
+use socket2::{SockRef, TcpKeepalive};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::{broadcast, Semaphore},
+};
 use tokio_stream::StreamExt as _;
 use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tracing::{info, info_span, warn, Instrument as _, Span};
 
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecode, RespEncode, RespError, RespFrame,
+    cmd::{
+        command_name, is_write, replicated_write, restore_commands, Command, CommandExecutor,
+        ConnectionContext,
+    },
+    resp::frame_length,
+    Backend, BackendSnapshot, BulkString, RespArray, RespDecode, RespEncode, RespFrame,
+    SimpleError, SimpleString,
 };
 
+// first bytes of every typed RESP frame, anything else is an inline command
+const RESP_PREFIXES: &[u8] = b"+-:$*_#,(!=%~>";
+
 #[derive(Debug)]
 struct RedisRequest {
     frame: RespFrame,
@@ -19,38 +41,496 @@ struct RedisRequest {
 #[derive(Debug)]
 struct RedisResponse {
     frame: RespFrame,
+    // the client asked to stream every executed command from now on
+    monitor: bool,
+    // the client is a replica asking for the data set and every write from now on
+    sync: bool,
 }
 
+// same as proto-max-bulk-len in redis, the largest string a client can send
+const DEFAULT_MAX_FRAME_SIZE: usize = 512 * 1024 * 1024;
+
+// frames are encoded in the RESP version the peer negotiated
 #[derive(Debug)]
-struct RespFrameCodec;
+pub(crate) struct RespFrameCodec {
+    protocol: u8,
+    // bytes buffered for a frame that is still incomplete before the peer is cut off
+    max_frame_size: usize,
+}
+
+impl RespFrameCodec {
+    pub(crate) fn new(protocol: u8) -> Self {
+        Self {
+            protocol,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+
+    pub(crate) fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    pub(crate) fn set_protocol(&mut self, protocol: u8) {
+        self.protocol = protocol;
+    }
+}
+
+impl Default for RespFrameCodec {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Per connection settings, `None` disables the corresponding timeout.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionConfig {
+    /// close the connection when the client sends nothing for this long
+    pub idle_timeout: Option<Duration>,
+    /// drop the client when a reply cannot be flushed within this window
+    pub write_timeout: Option<Duration>,
+    /// close the connection when a single request grows past this many bytes
+    pub max_frame_size: usize,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: None,
+            write_timeout: None,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+}
+
+/// Server wide settings applied by [`serve`].
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    /// connections beyond this limit are rejected with an error reply
+    pub max_clients: usize,
+    pub connection: ConnectionConfig,
+    /// send small replies right away instead of waiting to coalesce them (Nagle)
+    pub tcp_nodelay: bool,
+    /// probe idle tcp peers after this long so half-open connections get closed
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_clients: 10000,
+            connection: ConnectionConfig::default(),
+            tcp_nodelay: true,
+            // same default as tcp-keepalive in redis
+            tcp_keepalive: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+// the transports the server can accept connections from
+trait Listener {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
 
-pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
-    let mut framed = Framed::new(stream, RespFrameCodec);
+    fn accept(&self) -> impl Future<Output = io::Result<(Self::Stream, String)>> + Send;
 
+    // applies the transport specific socket options of the config to an accepted stream
+    fn configure(&self, _stream: &Self::Stream, _config: &ServerConfig) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    async fn accept(&self) -> io::Result<(Self::Stream, String)> {
+        let (stream, addr) = TcpListener::accept(self).await?;
+        Ok((stream, addr.to_string()))
+    }
+
+    fn configure(&self, stream: &Self::Stream, config: &ServerConfig) -> io::Result<()> {
+        stream.set_nodelay(config.tcp_nodelay)?;
+        let socket = SockRef::from(stream);
+        match config.tcp_keepalive {
+            Some(time) => socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(time)),
+            None => socket.set_keepalive(false),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    async fn accept(&self) -> io::Result<(Self::Stream, String)> {
+        // unix clients are unnamed, report the socket path they came through
+        let (stream, _) = UnixListener::accept(self).await?;
+        let path = self.local_addr()?;
+        let path = path.as_pathname().unwrap_or("unix".as_ref());
+        Ok((stream, format!("{}:0", path.display())))
+    }
+}
+
+/// Accept connections on the listener and serve each one on its own task.
+pub async fn serve(listener: TcpListener, backend: Backend, config: ServerConfig) -> Result<()> {
+    accept_loop(listener, backend, config, stream_handler).await
+}
+
+/// Same as [`serve`] for local clients connecting through a unix domain socket.
+#[cfg(unix)]
+pub async fn serve_unix(
+    listener: UnixListener,
+    backend: Backend,
+    config: ServerConfig,
+) -> Result<()> {
+    accept_loop(listener, backend, config, stream_handler).await
+}
+
+/// Same as [`serve`] but every connection goes through a TLS handshake first.
+#[cfg(feature = "tls")]
+pub async fn serve_tls(
+    listener: TcpListener,
+    backend: Backend,
+    config: ServerConfig,
+    acceptor: tokio_rustls::TlsAcceptor,
+) -> Result<()> {
+    accept_loop(
+        listener,
+        backend,
+        config,
+        move |stream, peer, backend, config| {
+            let acceptor = acceptor.clone();
+            async move {
+                let stream = acceptor.accept(stream).await?;
+                stream_handler(stream, peer, backend, config).await
+            }
+        },
+    )
+    .await
+}
+
+async fn accept_loop<L, F, Fut>(
+    listener: L,
+    backend: Backend,
+    config: ServerConfig,
+    handler: F,
+) -> Result<()>
+where
+    L: Listener,
+    F: Fn(L::Stream, String, Backend, ConnectionConfig) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let permits = Arc::new(Semaphore::new(config.max_clients));
     loop {
-        match framed.next().await {
+        let (stream, peer_addr) = listener.accept().await?;
+        info!("Accepted connection from {}", peer_addr);
+        // the connection is still usable with the default options
+        if let Err(e) = listener.configure(&stream, &config) {
+            warn!("Failed to set socket options for {}: {}", peer_addr, e);
+        }
+
+        // the permit is held for the whole lifetime of the connection
+        let permit = match permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                warn!("Rejecting {}: max number of clients reached", peer_addr);
+                tokio::spawn(reject(stream));
+                continue;
+            }
+        };
+        let connection = handler(
+            stream,
+            peer_addr.clone(),
+            backend.clone(),
+            config.connection,
+        );
+        tokio::spawn(async move {
+            match connection.await {
+                Ok(_) => info!("Connection from {} closed", peer_addr),
+                Err(e) => warn!("Error {} occurs while handle {} connection", e, peer_addr),
+            }
+            drop(permit);
+        });
+    }
+}
+
+// every log line of a connection carries its id and peer address
+fn connection_span(id: u64, peer: &str) -> Span {
+    info_span!("connection", id, peer = %peer)
+}
+
+async fn reject<S: AsyncWrite + Unpin>(mut stream: S) {
+    let _ = stream
+        .write_all(b"-ERR max number of clients reached\r\n")
+        .await;
+    let _ = stream.shutdown().await;
+}
+
+pub async fn stream_handler<S>(
+    stream: S,
+    peer: String,
+    backend: Backend,
+    config: ConnectionConfig,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let context = ConnectionContext::new(backend.next_client_id());
+    let span = connection_span(context.id, &peer);
+    // unlisted however the connection ends
+    let registration = backend.register_client(context.id, peer.clone());
+    async {
+        tokio::select! {
+            result = serve_connection(stream, peer, context, backend, config) => result,
+            _ = registration.killed() => {
+                info!("Connection killed by CLIENT KILL");
+                Ok(())
+            }
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    peer: String,
+    mut context: ConnectionContext,
+    backend: Backend,
+    config: ConnectionConfig,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let codec = RespFrameCodec::new(context.protocol).with_max_frame_size(config.max_frame_size);
+    let mut framed = Framed::new(stream, codec);
+
+    loop {
+        let next = match config.idle_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, framed.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    info!("Connection idle for {:?}, closing", timeout);
+                    return Ok(());
+                }
+            },
+            None => framed.next().await,
+        };
+        match next {
+            // empty requests are skipped like blank inline lines, redis reads the next command
+            Some(Ok(RespFrame::Array(args))) if args.is_empty() => continue,
             Some(Ok(frame)) => {
-                info!("received frame: {:?}", frame);
+                info!("received frame:\n{}", frame);
+                if backend.is_monitored() {
+                    backend.publish_monitor(monitor_line(&frame, &peer));
+                }
                 let request = RedisRequest {
                     frame,
                     backend: backend.clone(),
                 };
-                let response = handle_request(request).await?;
-                info!("Sending response: {:?}", response.frame);
-                framed.send(response.frame).await?;
+                let response = handle_request(request, &mut context).await?;
+                // HELLO answers in the protocol it switches to
+                framed.codec_mut().set_protocol(context.protocol);
+                // subscribe before replying so nothing executed after the OK is missed
+                let monitor = response.monitor.then(|| backend.subscribe_monitor());
+                let replication = response.sync.then(|| backend.sync_replica());
+                info!("Sending response:\n{}", response.frame);
+                send(&mut framed, response.frame, config).await?;
+                if let Some(monitor) = monitor {
+                    if !monitor_stream(&mut framed, monitor, &backend, &mut context, config).await?
+                    {
+                        return Ok(());
+                    }
+                }
+                if let Some((snapshot, writes)) = replication {
+                    return replica_stream(&mut framed, snapshot, writes, &backend, config).await;
+                }
+            }
+            Some(Err(e)) => {
+                // tell the client why it is disconnected, the connection is unusable anyway
+                let reply = SimpleError::new(format!("ERR Protocol error: {}", e));
+                let _ = send(&mut framed, reply.into(), config).await;
+                return Err(e);
             }
-            Some(Err(e)) => return Err(e),
             None => return Ok(()),
         }
     }
 }
 
-async fn handle_request(request: RedisRequest) -> Result<RedisResponse> {
+async fn send<S>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    frame: RespFrame,
+    config: ConnectionConfig,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match config.write_timeout {
+        Some(timeout) => tokio::time::timeout(timeout, framed.send(frame))
+            .await
+            .map_err(|_| anyhow!("write timed out after {:?}", timeout))?,
+        None => framed.send(frame).await,
+    }
+}
+
+// a monitoring client only receives, its own commands are ignored until it sends RESET,
+// returns whether the connection goes back to normal mode
+async fn monitor_stream<S>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    mut lines: broadcast::Receiver<String>,
+    backend: &Backend,
+    context: &mut ConnectionContext,
+    config: ConnectionConfig,
+) -> Result<bool>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        tokio::select! {
+            line = lines.recv() => match line {
+                Ok(line) => send(framed, SimpleString::new(line).into(), config).await?,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("Monitor client lagged behind, {} commands dropped", n)
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(false),
+            },
+            next = framed.next() => match next {
+                Some(Ok(frame)) => {
+                    if let Ok(Command::Reset(reset)) = Command::try_from(frame) {
+                        let reply = reset.execute_async(backend, context).await;
+                        framed.codec_mut().set_protocol(context.protocol);
+                        send(framed, reply, config).await?;
+                        return Ok(true);
+                    }
+                }
+                Some(Err(e)) => return Err(e),
+                None => return Ok(false),
+            },
+        }
+    }
+}
+
+// the full data set goes first, then every write executed after the snapshot was taken
+async fn replica_stream<S>(
+    framed: &mut Framed<S, RespFrameCodec>,
+    snapshot: BackendSnapshot,
+    mut writes: broadcast::Receiver<RespFrame>,
+    backend: &Backend,
+    config: ConnectionConfig,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    for command in restore_commands(backend, snapshot) {
+        send(framed, command, config).await?;
+    }
+    loop {
+        tokio::select! {
+            write = writes.recv() => match write {
+                Ok(command) => send(framed, command, config).await?,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    return Err(anyhow!("replica lagged behind, {} writes dropped", n))
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            // replicas never send anything after SYNC, this only notices them leaving
+            next = framed.next() => match next {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Follow the primary at `addr`: load its data set, then apply every write it streams.
+pub(crate) async fn replicate(addr: String, backend: Backend) {
+    match follow_primary(&addr, &backend).await {
+        Ok(_) => info!("Primary {} closed the replication stream", addr),
+        Err(e) => warn!("Replication from {} stopped: {}", addr, e),
+    }
+    backend.follow_ended(tokio::task::id());
+}
+
+async fn follow_primary(addr: &str, backend: &Backend) -> Result<()> {
+    let stream = TcpStream::connect(addr).await?;
+    let mut framed = Framed::new(stream, RespFrameCodec::default());
+    framed.send(RespArray::command(&[b"sync"]).into()).await?;
+    info!("Replicating from {}", addr);
+
+    while let Some(frame) = framed.next().await {
+        match frame? {
+            RespFrame::SimpleString(reply) if reply.0 == "FULLRESYNC" => backend.flush(),
+            RespFrame::Array(command) => {
+                let cmd = Command::try_from(RespFrame::Array(command.clone()))?;
+                // applied like our own writes, which also passes them on to our replicas
+                let reply = backend.execute_write(|| cmd.execute(backend), || command.into());
+                if let RespFrame::SimpleError(e) = reply {
+                    warn!("Failed to apply a write from {}: {}", addr, e);
+                }
+            }
+            frame => return Err(anyhow!("unexpected frame from primary: {:?}", frame)),
+        }
+    }
+    Ok(())
+}
+
+// formatted like redis: 1339518083.107412 [0 127.0.0.1:60866] "set" "foo" "bar"
+fn monitor_line(frame: &RespFrame, peer: &str) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut line = format!("{}.{:06} [0 {}]", now.as_secs(), now.subsec_micros(), peer);
+    if let RespFrame::Array(args) = frame {
+        for arg in args.iter() {
+            match arg {
+                RespFrame::BulkString(arg) => line.push_str(&format!(" {}", arg)),
+                arg => line.push_str(&format!(" \"{}\"", arg)),
+            }
+        }
+    }
+    line
+}
+
+async fn handle_request(
+    request: RedisRequest,
+    context: &mut ConnectionContext,
+) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
+    let name = command_name(&frame);
+    // kept for the slowlog, the arguments are reference counted so this is cheap
+    let args = match frame {
+        RespFrame::Array(ref args) => Some(args.clone()),
+        _ => None,
+    };
     let cmd = Command::try_from(frame)?;
     info!("Executing command: {:?}", cmd);
-    let resp_frame = cmd.execute(&backend);
-    Ok(RedisResponse { frame: resp_frame })
+    let monitor = matches!(cmd, Command::Monitor(_));
+    let sync = matches!(cmd, Command::FullSync(_));
+    let start = Instant::now();
+    let resp_frame = match args.as_ref().filter(|_| name.is_some_and(is_write)) {
+        // the data set of a replica only changes through its primary
+        Some(_) if backend.is_replica() => {
+            SimpleError::new("READONLY You can't write against a read only replica.").into()
+        }
+        // writes never await, so each one is applied and propagated in a single step
+        Some(args) => backend.execute_write(
+            || cmd.execute(&backend),
+            || replicated_write(args, &backend).into(),
+        ),
+        None => cmd.execute_async(&backend, context).await,
+    };
+    let elapsed = start.elapsed();
+    if let Some(name) = name {
+        backend.record_command(name, elapsed);
+    }
+    if let Some(args) = args.filter(|_| backend.is_slow(elapsed)) {
+        backend.record_slow_command(args, elapsed);
+    }
+    Ok(RedisResponse {
+        frame: resp_frame,
+        monitor,
+        sync,
+    })
 }
 
 impl Decoder for RespFrameCodec {
@@ -62,10 +542,42 @@ impl Decoder for RespFrameCodec {
         &mut self,
         src: &mut bytes::BytesMut,
     ) -> std::result::Result<Option<Self::Item>, Self::Error> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::Incomplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        if let Some(first) = src.first() {
+            if !RESP_PREFIXES.contains(first) {
+                if let Some(frame) = decode_inline(src) {
+                    return Ok(Some(frame));
+                }
+                if src.len() > self.max_frame_size {
+                    return Err(anyhow!("too big inline request"));
+                }
+            }
+        }
+        // nested decoders consume bytes before they can tell a frame is incomplete,
+        // so only decode once the whole frame has arrived
+        match frame_length(src)? {
+            Some(_) => Ok(Some(RespFrame::decode(src)?)),
+            None if src.len() > self.max_frame_size => Err(anyhow!("too big request")),
+            None => Ok(None),
+        }
+    }
+}
+
+// inline commands are whitespace separated and terminated by a newline, e.g. `set foo bar\r\n`
+fn decode_inline(src: &mut bytes::BytesMut) -> Option<RespFrame> {
+    loop {
+        let end = src.iter().position(|b| *b == b'\n')?;
+        let line = src.split_to(end + 1);
+        let args = line[..]
+            .split(|b: &u8| b.is_ascii_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| BulkString::new(arg).into())
+            .collect::<Vec<RespFrame>>();
+        // blank lines are skipped
+        if !args.is_empty() {
+            return Some(RespArray::new(args).into());
+        }
+        if src.is_empty() || RESP_PREFIXES.contains(&src[0]) {
+            return None;
         }
     }
 }
@@ -78,8 +590,901 @@ impl Encoder<RespFrame> for RespFrameCodec {
         item: RespFrame,
         dst: &mut bytes::BytesMut,
     ) -> std::result::Result<(), Self::Error> {
-        let encoded = item.encode();
-        dst.extend_from_slice(&encoded);
+        let item = if self.protocol < 3 {
+            item.into_resp2()
+        } else {
+            item.into_resp3()
+        };
+        dst.extend_from_slice(&item.encode());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use futures::SinkExt as _;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_stream::StreamExt as _;
+    use tokio_util::codec::{Decoder, Encoder, Framed};
+    use tracing_subscriber::EnvFilter;
+
+    use socket2::SockRef;
+
+    use super::{
+        handle_request, replicate, serve, stream_handler, ConnectionConfig, ConnectionContext,
+        Listener, RedisRequest, RespFrameCodec, ServerConfig,
+    };
+    use crate::{Backend, BulkString, RespArray, RespEncode, RespFrame, SimpleString};
+
+    #[test]
+    fn test_decode_inline_command() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"ping\r\n"[..]);
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new(vec![BulkString::new(b"ping").into()]).into())
+        );
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b"\r\n*1\r\n$4\r\nping\r\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new(vec![BulkString::new(b"ping").into()]).into())
+        );
+
+        buf.extend_from_slice(b"\r\nset  foo bar\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(RespFrame::Array(RespArray::new(vec![
+                BulkString::new(b"set").into(),
+                BulkString::new(b"foo").into(),
+                BulkString::new(b"bar").into(),
+            ])))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_array_split_across_reads() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::new();
+
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nhel");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(buf.len(), 20);
+
+        buf.extend_from_slice(b"lo\r\n$5\r\nwo");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(buf.len(), 30);
+
+        buf.extend_from_slice(b"rld\r\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(
+                RespArray::new(vec![
+                    BulkString::new(b"set").into(),
+                    BulkString::new(b"hello").into(),
+                    BulkString::new(b"world").into(),
+                ])
+                .into()
+            )
+        );
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_large_argument_in_place() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let payload = vec![b'x'; 1024 * 1024];
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(format!("*2\r\n$3\r\nset\r\n${}\r\n", payload.len()).as_bytes());
+        let header_len = buf.len();
+        buf.extend_from_slice(&payload[..1024]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+
+        buf.extend_from_slice(&payload[1024..]);
+        buf.extend_from_slice(b"\r\n");
+        let data_ptr = buf[header_len..].as_ptr();
+        let Some(RespFrame::Array(args)) = codec.decode(&mut buf)? else {
+            panic!("expected an array");
+        };
+        // the argument is a view of the read buffer, not a copy of it
+        let RespFrame::BulkString(arg) = &args[1] else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(arg.as_ref().as_ptr(), data_ptr);
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_partial_inline_command() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = BytesMut::from(&b"get fo"[..]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"o\r\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(
+                RespArray::new(vec![
+                    BulkString::new(b"get").into(),
+                    BulkString::new(b"foo").into()
+                ])
+                .into()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_rejects_oversized_partial_frame() -> Result<()> {
+        let mut codec = RespFrameCodec::default().with_max_frame_size(16);
+        // the declared length is fine, but the bytes buffered so far exceed the cap
+        let mut buf = BytesMut::from(&b"*1\r\n$1000\r\naaaa"[..]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"aaaaaaaa");
+        assert!(codec.decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(&b"set key aaaaaaaaaaaaaaaa"[..]);
+        assert!(codec.decode(&mut buf).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_oversized_request_closes_connection() -> Result<()> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let config = ConnectionConfig {
+            max_frame_size: 64,
+            ..Default::default()
+        };
+        let handler = tokio::spawn(stream_handler(
+            server,
+            "test".to_string(),
+            Backend::default(),
+            config,
+        ));
+
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$4096\r\n")
+            .await?;
+        client.write_all(&[b'a'; 128]).await?;
+        let mut reply = vec![];
+        client.read_to_end(&mut reply).await?;
+        assert_eq!(reply, b"-ERR Protocol error: too big request\r\n");
+        assert!(handler.await?.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_sleep_replies_ok() -> Result<()> {
+        let request = RedisRequest {
+            frame: RespArray::new(vec![
+                BulkString::new(b"debug").into(),
+                BulkString::new(b"sleep").into(),
+                BulkString::new(b"0").into(),
+            ])
+            .into(),
+            backend: Backend::default(),
+        };
+        let response = handle_request(request, &mut ConnectionContext::default()).await?;
+        assert_eq!(response.frame, SimpleString::new("OK".to_string()).into());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_key_is_null_bulk_string_in_resp2() -> Result<()> {
+        let request = RedisRequest {
+            frame: RespArray::command(&[b"get", b"missing"]).into(),
+            backend: Backend::default(),
+        };
+        let response = handle_request(request, &mut ConnectionContext::default()).await?;
+        let mut buf = BytesMut::new();
+        RespFrameCodec::new(2).encode(response.frame.clone(), &mut buf)?;
+        assert_eq!(&buf[..], b"$-1\r\n");
+
+        let mut buf = BytesMut::new();
+        RespFrameCodec::new(3).encode(response.frame, &mut buf)?;
+        assert_eq!(&buf[..], b"_\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_stats_are_counted() -> Result<()> {
+        let backend = Backend::default();
+        let requests: [&[&[u8]]; 5] = [
+            &[b"set", b"foo", b"bar"],
+            &[b"get", b"foo"],
+            &[b"get", b"foo"],
+            &[b"get", b"missing"],
+            &[b"nosuchcommand"],
+        ];
+        for parts in requests {
+            let request = RedisRequest {
+                frame: RespArray::command(parts).into(),
+                backend: backend.clone(),
+            };
+            handle_request(request, &mut ConnectionContext::default()).await?;
+        }
+
+        let stats = backend
+            .command_stats()
+            .into_iter()
+            .map(|(name, stats)| (name, stats.calls))
+            .collect::<Vec<_>>();
+        assert_eq!(stats, vec![("get", 3), ("set", 1)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slow_command_is_logged() -> Result<()> {
+        let backend = Backend::default();
+        backend.set_slowlog_threshold(Some(Duration::from_millis(20)));
+        let mut context = ConnectionContext::default();
+        let request = |parts: &[&[u8]]| RedisRequest {
+            frame: RespArray::command(parts).into(),
+            backend: backend.clone(),
+        };
+        handle_request(request(&[b"set", b"fast", b"1"]), &mut context).await?;
+        handle_request(request(&[b"debug", b"sleep", b"0.05"]), &mut context).await?;
+        let log = handle_request(request(&[b"slowlog", b"get"]), &mut context)
+            .await?
+            .frame;
+        let RespFrame::Array(entries) = log else {
+            panic!("expected an array, got {:?}", log);
+        };
+        assert_eq!(entries.len(), 1);
+        let RespFrame::Array(ref entry) = entries[0] else {
+            panic!("expected an entry, got {:?}", entries[0]);
+        };
+        assert_eq!(entry[0], RespFrame::Integer(0));
+        assert!(matches!(entry[2], RespFrame::Integer(usec) if usec >= 50_000));
+        assert_eq!(
+            entry[3],
+            RespArray::command(&[b"debug", b"sleep", b"0.05"]).into()
+        );
+
+        handle_request(request(&[b"slowlog", b"reset"]), &mut context).await?;
+        let len = handle_request(request(&[b"slowlog", b"len"]), &mut context)
+            .await?
+            .frame;
+        assert_eq!(len, RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_null_follows_negotiated_protocol() -> Result<()> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let handler = tokio::spawn(stream_handler(
+            server,
+            "test".to_string(),
+            Backend::default(),
+            Default::default(),
+        ));
+        let get = b"*2\r\n$3\r\nget\r\n$7\r\nmissing\r\n";
+
+        // connections start in RESP2
+        client.write_all(get).await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"$-1\r\n");
+
+        client
+            .write_all(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n")
+            .await?;
+        let mut codec = RespFrameCodec::default();
+        let mut reply = BytesMut::new();
+        while codec.decode(&mut reply)?.is_none() {
+            let mut chunk = [0u8; 64];
+            let n = client.read(&mut chunk).await?;
+            reply.extend_from_slice(&chunk[..n]);
+        }
+
+        client.write_all(get).await?;
+        let mut buf = [0u8; 3];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"_\r\n");
+
+        drop(client);
+        handler.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_list_shows_connections() -> Result<()> {
+        let backend = Backend::default();
+        let (mut first, server) = tokio::io::duplex(1024);
+        let first_handler = tokio::spawn(stream_handler(
+            server,
+            "10.0.0.1:5000".to_string(),
+            backend.clone(),
+            Default::default(),
+        ));
+        let (mut second, server) = tokio::io::duplex(1024);
+        let second_handler = tokio::spawn(stream_handler(
+            server,
+            "10.0.0.2:6000".to_string(),
+            backend.clone(),
+            Default::default(),
+        ));
+
+        second
+            .write_all(b"*3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$6\r\nworker\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        second.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        first
+            .write_all(b"*2\r\n$6\r\nclient\r\n$4\r\nlist\r\n")
+            .await?;
+        let mut codec = RespFrameCodec::default();
+        let mut reply = BytesMut::new();
+        let list = loop {
+            if let Some(frame) = codec.decode(&mut reply)? {
+                break frame;
+            }
+            let mut chunk = [0u8; 256];
+            let n = first.read(&mut chunk).await?;
+            reply.extend_from_slice(&chunk[..n]);
+        };
+        let expected = "id=1 addr=10.0.0.1:5000 name= db=0\n\
+                        id=2 addr=10.0.0.2:6000 name=worker db=0\n";
+        assert_eq!(list, BulkString::from(expected).into());
+
+        drop(second);
+        second_handler.await??;
+        assert_eq!(backend.clients().len(), 1);
+        drop(first);
+        first_handler.await??;
+        assert!(backend.clients().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill_closes_connection() -> Result<()> {
+        let backend = Backend::default();
+        let (_victim, server) = tokio::io::duplex(1024);
+        let victim_handler = tokio::spawn(stream_handler(
+            server,
+            "10.0.0.1:5000".to_string(),
+            backend.clone(),
+            Default::default(),
+        ));
+        let (mut killer, server) = tokio::io::duplex(1024);
+        let killer_handler = tokio::spawn(stream_handler(
+            server,
+            "10.0.0.2:6000".to_string(),
+            backend.clone(),
+            Default::default(),
+        ));
+
+        // wait for both handlers to register
+        while backend.clients().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        let victim = backend
+            .clients()
+            .into_iter()
+            .find(|c| c.addr == "10.0.0.1:5000")
+            .map(|c| c.id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("victim is not listed"))?;
+        let kill = RespArray::command(&[b"client", b"kill", b"id", victim.as_bytes()]);
+        killer.write_all(&RespFrame::from(kill).encode()).await?;
+        let mut buf = [0u8; 5];
+        killer.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b":+1\r\n");
+
+        // the victim's handler exits although its client is still open
+        tokio::time::timeout(Duration::from_secs(1), victim_handler).await???;
+        assert_eq!(backend.clients().len(), 1);
+
+        drop(killer);
+        killer_handler.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_times_out() -> Result<()> {
+        let (_client, server) = tokio::io::duplex(1024);
+        let config = ConnectionConfig {
+            idle_timeout: Some(Duration::from_millis(50)),
+            write_timeout: None,
+            ..Default::default()
+        };
+        let handler = stream_handler(server, "test".to_string(), Backend::default(), config);
+        // the client never sends anything, the handler must give up on its own
+        tokio::time::timeout(Duration::from_secs(1), handler).await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_active_connection_is_served() -> Result<()> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let config = ConnectionConfig {
+            idle_timeout: Some(Duration::from_millis(200)),
+            write_timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+        let handler = tokio::spawn(stream_handler(
+            server,
+            "test".to_string(),
+            Backend::default(),
+            config,
+        ));
+
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        drop(client);
+        handler.await??;
+        Ok(())
+    }
+
+    // collects formatted log lines so tests can inspect what a filter lets through
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn logs_with_filter(filter: &str) -> Result<String> {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::new(filter))
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let (mut client, server) = tokio::io::duplex(1024);
+        let handler = stream_handler(
+            server,
+            "10.0.0.1:4242".to_string(),
+            Backend::default(),
+            ConnectionConfig::default(),
+        );
+        let client = async move {
+            client
+                .write_all(b"*3\r\n$3\r\nset\r\n$3\r\nfoo\r\n$3\r\nbar\r\n")
+                .await?;
+            let mut buf = [0u8; 5];
+            client.read_exact(&mut buf).await?;
+            anyhow::Ok(())
+        };
+        let (served, sent) = tokio::join!(handler, client);
+        served?;
+        sent?;
+
+        let logs = logs.0.lock().unwrap().clone();
+        Ok(String::from_utf8(logs)?)
+    }
+
+    #[tokio::test]
+    async fn test_log_level_filter() -> Result<()> {
+        let logs = logs_with_filter("info").await?;
+        assert!(logs.contains("received frame"));
+        assert!(logs.contains("connection{id="));
+        assert!(logs.contains("peer=10.0.0.1:4242"));
+
+        let logs = logs_with_filter("warn").await?;
+        assert!(!logs.contains("received frame"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tcp_options_are_applied() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let client = TcpStream::connect(listener.local_addr()?).await?;
+        let (stream, _) = Listener::accept(&listener).await?;
+
+        listener.configure(&stream, &ServerConfig::default())?;
+        assert!(stream.nodelay()?);
+        assert!(SockRef::from(&stream).keepalive()?);
+
+        let config = ServerConfig {
+            tcp_nodelay: false,
+            tcp_keepalive: None,
+            ..Default::default()
+        };
+        listener.configure(&stream, &config)?;
+        assert!(!stream.nodelay()?);
+        assert!(!SockRef::from(&stream).keepalive()?);
+
+        // and the configured connection is still served
+        let handler = tokio::spawn(stream_handler(
+            stream,
+            "test".to_string(),
+            Backend::default(),
+            Default::default(),
+        ));
+        let mut client = client;
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+        drop(client);
+        handler.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ping_once_listening() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::default(), Default::default()));
+
+        let mut client = TcpStream::connect(addr).await?;
+        client.write_all(b"ping\r\n").await?;
+        let mut buf = [0u8; 7];
+        tokio::time::timeout(Duration::from_secs(1), client.read_exact(&mut buf)).await??;
+        assert_eq!(&buf, b"+PONG\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_array_is_skipped() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::default(), Default::default()));
+
+        let mut client = TcpStream::connect(addr).await?;
+        client.write_all(b"*0\r\n*1\r\n$4\r\nping\r\n").await?;
+        let mut buf = [0u8; 7];
+        tokio::time::timeout(Duration::from_secs(1), client.read_exact(&mut buf)).await??;
+        assert_eq!(&buf, b"+PONG\r\n");
+
+        // nothing was queued for the empty array, the next reply is the second PONG
+        client.write_all(b"*1\r\n$4\r\nping\r\n").await?;
+        tokio::time::timeout(Duration::from_secs(1), client.read_exact(&mut buf)).await??;
+        assert_eq!(&buf, b"+PONG\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_clients_rejects_extra_connections() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let config = ServerConfig {
+            max_clients: 1,
+            ..Default::default()
+        };
+        tokio::spawn(serve(listener, Backend::default(), config));
+
+        // make sure the first connection is being served before opening the second
+        let mut first = TcpStream::connect(addr).await?;
+        first
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        first.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        let mut second = TcpStream::connect(addr).await?;
+        let mut reply = vec![];
+        second.read_to_end(&mut reply).await?;
+        assert_eq!(reply, b"-ERR max number of clients reached\r\n");
+
+        // the slot is released once the first client leaves
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut third = TcpStream::connect(addr).await?;
+        third
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        let mut buf = [0u8; 11];
+        third.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"$5\r\nworld\r\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monitor_receives_other_connections_commands() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, Backend::default(), Default::default()));
+
+        let mut monitor = TcpStream::connect(addr).await?;
+        monitor.write_all(b"*1\r\n$7\r\nmonitor\r\n").await?;
+        let mut buf = [0u8; 5];
+        monitor.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        let mut worker = TcpStream::connect(addr).await?;
+        let worker_addr = worker.local_addr()?;
+        worker
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        worker.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        let mut line = vec![];
+        while !line.ends_with(b"\r\n") {
+            let mut byte = [0u8; 1];
+            monitor.read_exact(&mut byte).await?;
+            line.push(byte[0]);
+        }
+        let line = String::from_utf8(line)?;
+        assert!(line.starts_with('+'));
+        assert!(line.ends_with(&format!(
+            " [0 {}] \"set\" \"hello\" \"world\"\r\n",
+            worker_addr
+        )));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_leaves_monitor_mode() -> Result<()> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let handler = tokio::spawn(stream_handler(
+            server,
+            "test".to_string(),
+            Backend::default(),
+            Default::default(),
+        ));
+
+        client.write_all(b"*1\r\n$7\r\nmonitor\r\n").await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        client.write_all(b"*1\r\n$5\r\nreset\r\n").await?;
+        let mut buf = [0u8; 8];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+RESET\r\n");
+
+        // commands are executed again instead of being ignored
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        drop(client);
+        handler.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_switches_back_to_resp2() -> Result<()> {
+        let (mut client, server) = tokio::io::duplex(1024);
+        let handler = tokio::spawn(stream_handler(
+            server,
+            "test".to_string(),
+            Backend::default(),
+            Default::default(),
+        ));
+        let hello = b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n";
+        let get = b"*2\r\n$3\r\nget\r\n$7\r\nmissing\r\n";
+        let mut codec = RespFrameCodec::default();
+
+        // RESET out of a plain connection, then out of monitor mode
+        for monitor in [false, true] {
+            client.write_all(hello).await?;
+            let mut reply = BytesMut::new();
+            while codec.decode(&mut reply)?.is_none() {
+                let mut chunk = [0u8; 64];
+                let n = client.read(&mut chunk).await?;
+                reply.extend_from_slice(&chunk[..n]);
+            }
+            if monitor {
+                client.write_all(b"*1\r\n$7\r\nmonitor\r\n").await?;
+                let mut buf = [0u8; 5];
+                client.read_exact(&mut buf).await?;
+                assert_eq!(&buf, b"+OK\r\n");
+            }
+
+            client.write_all(b"*1\r\n$5\r\nreset\r\n").await?;
+            let mut buf = [0u8; 8];
+            client.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"+RESET\r\n");
+
+            client.write_all(get).await?;
+            let mut buf = [0u8; 5];
+            client.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"$-1\r\n");
+        }
+
+        drop(client);
+        handler.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replica_follows_primary() -> Result<()> {
+        let primary = Backend::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let primary_addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, primary.clone(), Default::default()));
+
+        let replica = Backend::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let replica_addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, replica.clone(), Default::default()));
+
+        // written before the replica connects, it arrives through the initial sync
+        primary.set("before".to_string(), BulkString::new(b"1").into());
+        replica.set("stale".to_string(), BulkString::new(b"x").into());
+
+        let mut client = TcpStream::connect(replica_addr).await?;
+        let port = primary_addr.port().to_string();
+        let request = RespArray::command(&[b"replicaof", b"127.0.0.1", port.as_bytes()]);
+        client.write_all(&request.encode()).await?;
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        let mut writer = TcpStream::connect(primary_addr).await?;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        // writes only flow once the replica has subscribed, so retry until it shows up
+        while replica.get("after").is_none() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "write never replicated"
+            );
+            writer
+                .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nafter\r\n$1\r\n2\r\n")
+                .await?;
+            writer.read_exact(&mut buf).await?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(replica.get("before"), Some(BulkString::new(b"1").into()));
+        assert_eq!(replica.get("after"), Some(BulkString::new(b"2").into()));
+        assert_eq!(replica.get("stale"), None);
+
+        // clients cannot write to the replica directly
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nafter\r\n$1\r\n3\r\n")
+            .await?;
+        let expected = b"-READONLY You can't write against a read only replica.\r\n";
+        let mut buf = vec![0u8; expected.len()];
+        client.read_exact(&mut buf).await?;
+        assert_eq!(buf, expected);
+        assert_eq!(replica.get("after"), Some(BulkString::new(b"2").into()));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_writes_during_sync_are_replicated_once() -> Result<()> {
+        const WRITERS: usize = 4;
+
+        let primary = Backend::default();
+        // a large data set keeps the snapshot busy while the counter is incremented
+        for i in 0..2_000 {
+            primary.set(format!("key:{}", i), BulkString::new(b"x").into());
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let primary_addr = listener.local_addr()?;
+        tokio::spawn(serve(listener, primary.clone(), Default::default()));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut writers = Vec::new();
+        for _ in 0..WRITERS {
+            let stream = TcpStream::connect(primary_addr).await?;
+            let stop = stop.clone();
+            writers.push(tokio::spawn(async move {
+                let mut framed = Framed::new(stream, RespFrameCodec::default());
+                let mut incrs = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    framed
+                        .send(RespArray::command(&[b"incr", b"n"]).into())
+                        .await?;
+                    framed.next().await.transpose()?;
+                    incrs += 1;
+                    // paced so the replica stream never lags far enough to drop writes
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                anyhow::Ok(incrs)
+            }));
+        }
+
+        let replica = Backend::default();
+        tokio::spawn(replicate(primary_addr.to_string(), replica.clone()));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while replica.get("n").is_none() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "sync never finished"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.store(true, Ordering::Relaxed);
+        let mut total = 0;
+        for writer in writers {
+            total += writer.await??;
+        }
+
+        // streamed after every INCR, so once it shows up the counter is final
+        let mut client = Framed::new(
+            TcpStream::connect(primary_addr).await?,
+            RespFrameCodec::default(),
+        );
+        client
+            .send(RespArray::command(&[b"set", b"done", b"1"]).into())
+            .await?;
+        client.next().await.transpose()?;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while replica.get("done").is_none() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "write never replicated"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let total = total.to_string();
+        assert_eq!(
+            replica.get("n"),
+            Some(BulkString::new(total.as_bytes()).into())
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_unix_socket() -> Result<()> {
+        use tokio::net::{UnixListener, UnixStream};
+
+        let path = std::env::temp_dir().join(format!("simple-redis-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        tokio::spawn(super::serve_unix(
+            listener,
+            Backend::default(),
+            Default::default(),
+        ));
+
+        let mut stream = UnixStream::connect(&path).await?;
+        stream
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        stream
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        let mut buf = [0u8; 11];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"$5\r\nworld\r\n");
+
+        std::fs::remove_file(&path)?;
         Ok(())
     }
 }
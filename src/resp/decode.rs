@@ -37,6 +37,8 @@ impl RespDecode for RespFrame {
                 let frame = SimpleString::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'-') => Ok(SimpleError::decode(buf)?.into()),
+            Some(b':') => Ok(i64::decode(buf)?.into()),
             Some(b'_') => {
                 let frame = RespNull::decode(buf)?;
                 Ok(frame.into())
@@ -49,7 +51,13 @@ impl RespDecode for RespFrame {
                 let frame = f64::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'$') if buf.starts_with(NullBulkString::PREFIX.as_bytes()) => {
+                Ok(NullBulkString::decode(buf)?.into())
+            }
             Some(b'$') => Ok(BulkString::decode(buf)?.into()),
+            Some(b'*') if buf.starts_with(RespNullArray::PREFIX.as_bytes()) => {
+                Ok(RespNullArray::decode(buf)?.into())
+            }
             Some(b'*') => Ok(RespArray::decode(buf)?.into()),
             Some(b'%') => Ok(RespMap::decode(buf)?.into()),
             Some(b'~') => Ok(RespSet::decode(buf)?.into()),
@@ -85,62 +93,66 @@ impl RespDecode for SimpleError {
 impl RespDecode for RespNull {
     const PREFIX: &'static str = "_";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if buf[0] == Self::PREFIX.as_bytes()[0] && buf.len() == 3 {
-            Ok(RespNull)
-        } else {
-            Err(RespError::InvalidFrameType(format!(
-                "expected prefix {:?} - but got {:?}",
-                Self::PREFIX,
-                buf[0]
-            )))
+        let crlf_idx = extra_simple_frame_data(Self::PREFIX, buf)?;
+        if crlf_idx != Self::PREFIX.len() {
+            return Err(RespError::InvalidFrame(format!(
+                "expected _\\r\\n, got {:?}",
+                &buf[..crlf_idx]
+            )));
         }
+        buf.advance(crlf_idx + CRLF_LEN);
+        Ok(RespNull)
     }
 }
 
+// *-1\r\n
 impl RespDecode for RespNullArray {
-    const PREFIX: &'static str = "_";
-
+    const PREFIX: &'static str = "*-1";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if buf.len() < 3 || buf[0] != Self::PREFIX.as_bytes()[0] {
-            Err(RespError::InvalidFrameType(format!(
-                "expected prefix {:?} - but got {:?}",
-                Self::PREFIX,
-                buf[0]
-            )))
-        } else {
-            Ok(RespNullArray)
-        }
+        decode_null(Self::PREFIX, buf)?;
+        Ok(RespNullArray)
     }
 }
 
 impl RespDecode for BulkString {
     const PREFIX: &'static str = "$";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if let Some(second_crlf_idx) = find_crlf(buf, 2) {
-            let crlf_1st_idx = extra_simple_frame_data(Self::PREFIX, buf)?;
-            let data = buf.split_to(second_crlf_idx + CRLF_LEN);
-            Ok(BulkString(
-                data[crlf_1st_idx + CRLF_LEN..second_crlf_idx].to_vec(),
-            ))
-        } else {
-            Err(RespError::Incomplete)
+        // take exactly the declared number of bytes, the payload may contain CRLF itself
+        let (crlf_1st_idx, len) = calc_total_length(buf, Self::PREFIX)?;
+        let data_start = crlf_1st_idx + CRLF_LEN;
+        // the length comes from the peer, so it may not fit in any buffer at all
+        let Some(frame_end) = data_start
+            .checked_add(len)
+            .and_then(|end| end.checked_add(CRLF_LEN))
+        else {
+            return Err(RespError::InvalidFrame(format!(
+                "bulk string length {} is out of range",
+                len
+            )));
+        };
+        let data_end = frame_end - CRLF_LEN;
+        if buf.len() < frame_end {
+            return Err(RespError::Incomplete);
+        }
+        if &buf[data_end..data_end + CRLF_LEN] != CRLF {
+            return Err(RespError::InvalidFrame(format!(
+                "bulk string of declared length {} is not terminated by CRLF",
+                len
+            )));
         }
+        // hand out a view of the read buffer instead of copying the payload
+        let mut data = buf.split_to(frame_end);
+        data.truncate(data_end);
+        data.advance(data_start);
+        Ok(BulkString(data.freeze()))
     }
 }
 // $-1\r\n
 impl RespDecode for NullBulkString {
     const PREFIX: &'static str = "$-1";
-
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if buf.len() < 3 && buf[0..3] != Self::PREFIX.as_bytes()[0..3] {
-            Err(RespError::InvalidFrameType(format!(
-                "expected prefix {:?} - but got {:?}",
-                Self::PREFIX,
-                buf[0]
-            )))
-        } else {
-            Ok(NullBulkString)
-        }
+        decode_null(Self::PREFIX, buf)?;
+        Ok(NullBulkString)
     }
 }
 
@@ -148,24 +160,10 @@ impl RespDecode for NullBulkString {
 impl RespDecode for i64 {
     const PREFIX: &'static str = ":";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let ret = if buf.len() < 3 && buf[0] != Self::PREFIX.as_bytes()[0] {
-            Err(RespError::InvalidFrameType(
-                String::from_utf8_lossy(buf).to_string(),
-            ))
-        } else {
-            let start_idx = 1;
-            let end_idx = buf.len() - 2;
-            let s = String::from_utf8_lossy(&buf[start_idx..end_idx]);
-            let res = if let Ok(i) = s.parse::<i64>() {
-                Ok(i)
-            } else {
-                Err(RespError::InvalidFrameType(
-                    String::from_utf8_lossy(buf).to_string(),
-                ))
-            };
-            res
-        };
-        ret
+        let crlf_end = extra_simple_frame_data(Self::PREFIX, buf)?;
+        let data = buf.split_to(crlf_end + CRLF_LEN);
+        let num_str = String::from_utf8_lossy(&data[Self::PREFIX.len()..crlf_end]);
+        Ok(num_str.parse::<i64>()?)
     }
 }
 
@@ -202,6 +200,20 @@ impl RespDecode for f64 {
     }
 }
 
+// the RESP2 nulls are a length of -1, nothing follows the prefix
+fn decode_null(prefix: &str, buf: &mut BytesMut) -> Result<(), RespError> {
+    let crlf_idx = extra_simple_frame_data(prefix, buf)?;
+    if crlf_idx != prefix.len() {
+        return Err(RespError::InvalidFrame(format!(
+            "expected {}\\r\\n, got {:?}",
+            prefix,
+            &buf[..crlf_idx]
+        )));
+    }
+    buf.advance(crlf_idx + CRLF_LEN);
+    Ok(())
+}
+
 fn extra_simple_frame_data(prefix: &str, buf: &mut BytesMut) -> Result<usize, RespError> {
     if buf.len() < 3 {
         return Err(RespError::Incomplete);
@@ -219,7 +231,7 @@ fn extra_simple_frame_data(prefix: &str, buf: &mut BytesMut) -> Result<usize, Re
 
 fn find_crlf(buf: &mut BytesMut, nth: i32) -> Option<usize> {
     let mut cnt = 0;
-    for i in 0..buf.len() {
+    for i in 0..buf.len().saturating_sub(1) {
         if buf[i] == b'\r' && buf[i + 1] == b'\n' {
             cnt += 1;
             if cnt == nth {
@@ -235,7 +247,8 @@ impl RespDecode for RespArray {
     const PREFIX: &'static str = "*";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let (crlf_1st_idx, elem_len) = calc_total_length(buf, Self::PREFIX)?;
-        let mut ret = Vec::with_capacity(elem_len);
+        // every element takes at least one byte, a bigger count is not backed by the buffer
+        let mut ret = Vec::with_capacity(elem_len.min(buf.len()));
         buf.advance(crlf_1st_idx + CRLF_LEN);
         for _ in 0..elem_len {
             let elem = RespFrame::decode(buf)?;
@@ -271,7 +284,7 @@ impl RespDecode for RespSet {
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
         let (crlf_1st_idx, len) = calc_total_length(buf, Self::PREFIX)?;
-        let mut frames = Vec::with_capacity(len);
+        let mut frames = Vec::with_capacity(len.min(buf.len()));
         buf.advance(crlf_1st_idx + CRLF_LEN);
         for _ in 0..len {
             frames.push(RespFrame::decode(buf)?);
@@ -280,6 +293,52 @@ impl RespDecode for RespSet {
     }
 }
 
+// Byte length of the first frame in `buf`, or `None` until all of it has arrived. Only the
+// headers are read, so a half received frame is never decoded (or copied) just to find
+// out it is incomplete.
+pub(crate) fn frame_length(buf: &[u8]) -> Result<Option<usize>, RespError> {
+    let mut pos = 0;
+    // frames still expected, nested aggregates add their elements as their header is read
+    let mut pending: usize = 1;
+    while pending > 0 {
+        pending -= 1;
+        let Some(crlf_idx) = find_crlf_from(buf, pos) else {
+            return Ok(None);
+        };
+        let header = &buf[pos..crlf_idx];
+        pos = crlf_idx + CRLF_LEN;
+        match header.first() {
+            Some(b'+' | b'-' | b':' | b'_' | b'#' | b',') => {}
+            Some(b'$') if header == NullBulkString::PREFIX.as_bytes() => {}
+            Some(b'*') if header == RespNullArray::PREFIX.as_bytes() => {}
+            Some(b'$') => {
+                pos = pos
+                    .saturating_add(parse_length(header)?)
+                    .saturating_add(CRLF_LEN);
+                if buf.len() < pos {
+                    return Ok(None);
+                }
+            }
+            Some(b'*' | b'~') => pending = pending.saturating_add(parse_length(header)?),
+            Some(b'%') => pending = pending.saturating_add(parse_length(header)?.saturating_mul(2)),
+            // same as RespFrame::decode, an unknown type is never complete
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(pos))
+}
+
+fn find_crlf_from(buf: &[u8], start: usize) -> Option<usize> {
+    buf.get(start..)?
+        .windows(CRLF_LEN)
+        .position(|window| window == CRLF)
+        .map(|idx| start + idx)
+}
+
+fn parse_length(header: &[u8]) -> Result<usize, RespError> {
+    Ok(String::from_utf8_lossy(&header[1..]).parse::<usize>()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,6 +375,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resp2_nulls_consume_buffer() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$-1\r\n*-1\r\n$0\r\n\r\n");
+        assert_eq!(RespFrame::decode(&mut buf)?, NullBulkString.into());
+        assert_eq!(RespFrame::decode(&mut buf)?, RespNullArray.into());
+        assert_eq!(RespFrame::decode(&mut buf)?, BulkString::new(b"").into());
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_resp_null_array_decode() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -325,6 +395,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_reply_frames_consume_buffer() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"_\r\n:+42\r\n-ERR boom\r\n:-7\r\n");
+        assert_eq!(RespFrame::decode(&mut buf)?, RespFrame::Null(RespNull));
+        assert_eq!(RespFrame::decode(&mut buf)?, RespFrame::Integer(42));
+        assert_eq!(
+            RespFrame::decode(&mut buf)?,
+            SimpleError::new("ERR boom").into()
+        );
+        assert_eq!(RespFrame::decode(&mut buf)?, RespFrame::Integer(-7));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_null_encode() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -349,6 +434,80 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_large_bulk_string_is_not_copied() -> Result<()> {
+        let payload = vec![b'x'; 1024 * 1024];
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(format!("${}\r\n", payload.len()).as_bytes());
+        let header_len = buf.len();
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(b"\r\n");
+        let data_ptr = buf[header_len..].as_ptr();
+
+        let frame = BulkString::decode(&mut buf)?;
+        // the payload still lives in the read buffer allocation
+        assert_eq!(frame.as_ref().as_ptr(), data_ptr);
+        assert_eq!(frame.as_ref(), payload.as_slice());
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_length() -> Result<()> {
+        let frame = b"*2\r\n$3\r\nget\r\n%1\r\n+k\r\n~2\r\n:1\r\n$-1\r\n";
+        for end in 0..frame.len() {
+            assert_eq!(frame_length(&frame[..end])?, None);
+        }
+        assert_eq!(frame_length(frame)?, Some(frame.len()));
+
+        let mut buf = frame.to_vec();
+        buf.extend_from_slice(b"+OK\r\n");
+        assert_eq!(frame_length(&buf)?, Some(frame.len()));
+
+        // the payload is skipped by its declared length, even when it contains CRLF
+        assert_eq!(frame_length(b"$4\r\na\r\nb\r\n")?, Some(10));
+        assert!(frame_length(b"*x\r\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_string_length_mismatch() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$2\r\nHello\r\n");
+        let ret = BulkString::decode(&mut buf);
+        assert!(matches!(ret, Err(RespError::InvalidFrame(_))));
+
+        // declared 5 but only 2 bytes arrived so far
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$5\r\nHi\r\n");
+        let ret = BulkString::decode(&mut buf);
+        assert!(matches!(ret, Err(RespError::Incomplete)));
+
+        // the end of the payload overflows usize
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(format!("${}\r\nHi\r\n", usize::MAX).as_bytes());
+        let ret = BulkString::decode(&mut buf);
+        assert!(matches!(ret, Err(RespError::InvalidFrame(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_string_binary_decode() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"$4\r\na\r\nb\r\n");
+        let frame = BulkString::decode(&mut buf)?;
+        assert_eq!(frame, BulkString::new(b"a\r\nb"));
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b"$4\r\na\r");
+        let ret = BulkString::decode(&mut buf);
+        assert!(matches!(ret, Err(RespError::Incomplete)));
+        buf.extend_from_slice(b"\nb\r\n");
+        let frame = BulkString::decode(&mut buf)?;
+        assert_eq!(frame, BulkString::new(b"a\r\nb"));
+        Ok(())
+    }
+
     #[test]
     fn test_boolean_decode() -> Result<()> {
         let mut buf = BytesMut::new();
@@ -399,6 +558,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_declared_count_is_not_preallocated() {
+        // would abort the process if the count was allocated up front
+        for data in [&b"*999999999999999\r\n"[..], b"~999999999999999\r\n"] {
+            let mut buf = BytesMut::from(data);
+            assert!(RespFrame::decode(&mut buf).is_err());
+        }
+    }
+
     #[test]
     fn test_respset_decode() -> Result<()> {
         let mut buf = BytesMut::new();
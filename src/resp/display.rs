@@ -0,0 +1,208 @@
+/*
+Human readable rendering of frames, following the redis-cli output format:
+
+Simple strings: OK
+Simple Errors: (error) ERR message
+Integers: (integer) 1
+Bulk strings: "hello"
+Nulls: (nil)
+Booleans: (true)
+Doubles: (double) 1.5
+Arrays: 1) "a"
+        2) 1) "nested"
+Maps: 1# "key" => "value"
+Sets: 1~ "member"
+
+*/
+
+use std::fmt;
+
+use super::{
+    BulkString, NullBulkString, RespArray, RespFrame, RespMap, RespNull, RespNullArray, RespSet,
+    SimpleError, SimpleString,
+};
+
+impl fmt::Display for RespFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RespFrame::SimpleString(s) => s.fmt(f),
+            RespFrame::SimpleError(e) => e.fmt(f),
+            RespFrame::Integer(i) => write!(f, "(integer) {}", i),
+            RespFrame::BulkString(s) => s.fmt(f),
+            RespFrame::NullBulkString(n) => n.fmt(f),
+            RespFrame::Array(a) => a.fmt(f),
+            RespFrame::Null(n) => n.fmt(f),
+            RespFrame::NullArray(n) => n.fmt(f),
+            RespFrame::Boolean(b) => write!(f, "({})", b),
+            RespFrame::Double(d) => write!(f, "(double) {}", d),
+            RespFrame::Map(m) => m.fmt(f),
+            RespFrame::Set(s) => s.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for SimpleString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for SimpleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(error) {}", self.0)
+    }
+}
+
+// quoted like redis-cli does, binary bytes are shown as \xNN
+impl fmt::Display for BulkString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        for &b in &self.0 {
+            match b {
+                b'"' => f.write_str("\\\"")?,
+                b'\\' => f.write_str("\\\\")?,
+                b'\n' => f.write_str("\\n")?,
+                b'\r' => f.write_str("\\r")?,
+                b'\t' => f.write_str("\\t")?,
+                b if b.is_ascii_graphic() || b == b' ' => write!(f, "{}", b as char)?,
+                b => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+impl fmt::Display for NullBulkString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(nil)")
+    }
+}
+
+impl fmt::Display for RespNull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(nil)")
+    }
+}
+
+impl fmt::Display for RespNullArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(nil)")
+    }
+}
+
+impl fmt::Display for RespArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(empty array)");
+        }
+        write_items(f, ')', self.0.iter().map(|frame| frame.to_string()))
+    }
+}
+
+impl fmt::Display for RespMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(empty hash)");
+        }
+        let entries = self.0.iter().map(|(key, value)| {
+            let key = BulkString::new(key.as_bytes());
+            format!("{} => {}", key, value)
+        });
+        write_items(f, '#', entries)
+    }
+}
+
+impl fmt::Display for RespSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(empty set)");
+        }
+        write_items(f, '~', self.0.iter().map(|frame| frame.to_string()))
+    }
+}
+
+// numbers every item and indents the continuation lines of nested aggregates under it
+fn write_items(
+    f: &mut fmt::Formatter<'_>,
+    marker: char,
+    items: impl ExactSizeIterator<Item = String>,
+) -> fmt::Result {
+    let width = items.len().to_string().len();
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            f.write_str("\n")?;
+        }
+        let prefix = format!("{:>width$}{} ", i + 1, marker, width = width);
+        for (j, line) in item.lines().enumerate() {
+            if j == 0 {
+                write!(f, "{}{}", prefix, line)?;
+            } else {
+                write!(f, "\n{:indent$}{}", "", line, indent = prefix.len())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_string_display() {
+        let s = BulkString::new(b"hello \"world\"\r\n\x00".to_vec());
+        assert_eq!(s.to_string(), r#""hello \"world\"\r\n\x00""#);
+    }
+
+    #[test]
+    fn test_scalar_display() {
+        assert_eq!(RespFrame::Integer(-3).to_string(), "(integer) -3");
+        assert_eq!(RespFrame::Null(RespNull).to_string(), "(nil)");
+        assert_eq!(
+            RespFrame::SimpleError(SimpleError::new("ERR oops")).to_string(),
+            "(error) ERR oops"
+        );
+        assert_eq!(
+            RespFrame::SimpleString(SimpleString::new("OK".to_string())).to_string(),
+            "OK"
+        );
+    }
+
+    #[test]
+    fn test_nested_array_display() {
+        let inner = RespArray::new(vec![
+            BulkString::new(b"field").into(),
+            RespFrame::Integer(7),
+        ]);
+        let mut items: Vec<RespFrame> = vec![BulkString::new(b"0").into(), inner.into()];
+        items.extend((0..8).map(|_| RespFrame::Null(RespNull)));
+        let frame: RespFrame = RespArray::new(items).into();
+        let expected = [
+            r#" 1) "0""#,
+            r#" 2) 1) "field""#,
+            "    2) (integer) 7",
+            " 3) (nil)",
+            " 4) (nil)",
+            " 5) (nil)",
+            " 6) (nil)",
+            " 7) (nil)",
+            " 8) (nil)",
+            " 9) (nil)",
+            "10) (nil)",
+        ]
+        .join("\n");
+        assert_eq!(frame.to_string(), expected);
+        assert_eq!(RespArray::new(vec![]).to_string(), "(empty array)");
+    }
+
+    #[test]
+    fn test_map_display() {
+        let mut map = RespMap::new();
+        map.insert("a".to_string(), RespFrame::Boolean(true));
+        map.insert("b".to_string(), RespFrame::Double(1.5));
+        assert_eq!(
+            RespFrame::Map(map).to_string(),
+            "1# \"a\" => (true)\n2# \"b\" => (double) 1.5"
+        );
+    }
+}
@@ -6,6 +6,7 @@ Simple Errors: -Error message\r\n
 Integers: :[<+|->]<value>\r\n
 Bulk strings: $<length>\r\n<data>\r\n  $5\r\nhello\r\n
 NullBulkStrings: $-1\r\n
+NullArrays: *-1\r\n
 Arrays: *<number-of-elements>\r\n<element-1>...<element-n>
 Nulls: _\r\n
 Booleans: #<t|f>\r\n
@@ -21,10 +22,64 @@ Sets: ~<number-of-elements>\r\n<element-1>...<element-n>
 const BUF_CAP: usize = 4096;
 
 use super::{
-    BulkString, NullBulkString, RespArray, RespEncode, RespMap, RespNull, RespNullArray, RespSet,
-    SimpleError, SimpleString,
+    BulkString, NullBulkString, RespArray, RespEncode, RespFrame, RespMap, RespNull, RespNullArray,
+    RespSet, SimpleError, SimpleString,
 };
 
+impl RespFrame {
+    /// The closest RESP2 form of a frame, RESP3 only types have no wire format there.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Null(_) => NullBulkString.into(),
+            RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+            RespFrame::Double(d) => BulkString::new(d.to_string()).into(),
+            RespFrame::Array(array) => resp2_array(array.0),
+            RespFrame::Set(set) => resp2_array(set.0),
+            RespFrame::Map(map) => {
+                let items = map
+                    .0
+                    .into_iter()
+                    .flat_map(|(key, value)| [BulkString::new(key).into(), value])
+                    .collect();
+                resp2_array(items)
+            }
+            frame => frame,
+        }
+    }
+
+    /// RESP3 has a single null type, the RESP2 null bulk string and null array become it.
+    pub fn into_resp3(self) -> RespFrame {
+        match self {
+            RespFrame::NullBulkString(_) | RespFrame::NullArray(_) => RespNull.into(),
+            RespFrame::Array(array) => {
+                RespArray(array.0.into_iter().map(RespFrame::into_resp3).collect()).into()
+            }
+            RespFrame::Set(set) => {
+                RespSet(set.0.into_iter().map(RespFrame::into_resp3).collect()).into()
+            }
+            RespFrame::Map(map) => RespMap(
+                map.0
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_resp3()))
+                    .collect(),
+            )
+            .into(),
+            frame => frame,
+        }
+    }
+}
+
+// maps are flattened into alternating keys and values
+fn resp2_array(items: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(
+        items
+            .into_iter()
+            .map(RespFrame::into_resp2)
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
 impl RespEncode for SimpleString {
     fn encode(self) -> Vec<u8> {
         format!("+{}\r\n", *self).into_bytes()
@@ -46,9 +101,12 @@ impl RespEncode for i64 {
 
 impl RespEncode for BulkString {
     fn encode(self) -> Vec<u8> {
-        let len = self.0.len();
-        let data = String::from_utf8_lossy(&self.0).to_string();
-        format!("${}\r\n{}\r\n", len, data).into_bytes()
+        // binary safe, the payload is written as is
+        let mut buf = Vec::with_capacity(self.0.len() + 16);
+        buf.extend_from_slice(format!("${}\r\n", self.0.len()).as_bytes());
+        buf.extend_from_slice(&self.0);
+        buf.extend_from_slice(b"\r\n");
+        buf
     }
 }
 
@@ -74,7 +132,7 @@ impl RespEncode for RespNull {
 // Null Arrays: _\r\n
 impl RespEncode for RespNullArray {
     fn encode(self) -> Vec<u8> {
-        b"_\r\n".to_vec()
+        b"*-1\r\n".to_vec()
     }
 }
 
@@ -135,8 +193,6 @@ impl RespEncode for f64 {
 #[cfg(test)]
 mod tests {
 
-    use crate::resp::RespFrame;
-
     use super::*;
 
     #[test]
@@ -197,7 +253,11 @@ mod tests {
     #[test]
     fn test_bulk_string_encode() {
         let frame: RespFrame = BulkString::new(b"hello").into();
-        assert_eq!(frame.encode(), b"$5\r\nhello\r\n")
+        assert_eq!(frame.encode(), b"$5\r\nhello\r\n");
+
+        // non utf8 payloads are written untouched
+        let frame: RespFrame = BulkString::new(vec![0xff, 0x00]).into();
+        assert_eq!(frame.encode(), b"$2\r\n\xff\x00\r\n");
     }
 
     #[test]
@@ -224,10 +284,39 @@ mod tests {
         assert_eq!(frame.encode(), b"_\r\n");
     }
 
+    #[test]
+    fn test_resp2_downgrade() {
+        let null: RespFrame = RespNull.into();
+        assert_eq!(null.clone().encode(), b"_\r\n");
+        assert_eq!(null.into_resp2().encode(), b"$-1\r\n");
+
+        let mut map = RespMap::new();
+        map.insert("ok".to_string(), RespFrame::Boolean(true));
+        map.insert("score".to_string(), RespFrame::Double(1.5));
+        let frame: RespFrame = RespSet::new(vec![map.into()]).into();
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*1\r\n*4\r\n$2\r\nok\r\n:+1\r\n$5\r\nscore\r\n$3\r\n1.5\r\n"
+        );
+    }
+
+    #[test]
+    fn test_resp3_nulls() {
+        let frame: RespFrame = RespArray::new(vec![
+            NullBulkString.into(),
+            RespNullArray.into(),
+            BulkString::new("x").into(),
+        ])
+        .into();
+        assert_eq!(frame.into_resp3().encode(), b"*3\r\n_\r\n_\r\n$1\r\nx\r\n");
+        let frame: RespFrame = NullBulkString.into();
+        assert_eq!(frame.into_resp3().encode(), b"_\r\n");
+    }
+
     #[test]
     fn test_null_array_encode() {
         let frame: RespFrame = RespNullArray.into();
-        assert_eq!(frame.encode(), b"_\r\n");
+        assert_eq!(frame.encode(), b"*-1\r\n");
     }
 
     #[test]
@@ -0,0 +1,134 @@
+// encode -> decode round trip over randomly generated frames, seeded so failures reproduce
+
+use anyhow::Result;
+use bytes::BytesMut;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    BulkString, NullBulkString, RespArray, RespFrame, RespMap, RespNull, RespNullArray, RespSet,
+    SimpleError, SimpleString,
+};
+use crate::{RespDecode, RespEncode};
+
+// aggregates nest at most this deep so generated frames stay small
+const MAX_DEPTH: usize = 3;
+
+/// A random frame of any variant, `depth` bounds how deep aggregates may nest.
+pub(crate) fn arbitrary_frame(rng: &mut impl Rng, depth: usize) -> RespFrame {
+    let variants = if depth == 0 { 9 } else { 12 };
+    match rng.gen_range(0..variants) {
+        0 => SimpleString::new(line(rng)).into(),
+        1 => SimpleError::new(line(rng)).into(),
+        2 => RespFrame::Integer(integer(rng)),
+        3 => {
+            let len = rng.gen_range(0..32);
+            BulkString::new((0..len).map(|_| rng.gen::<u8>()).collect::<Vec<u8>>()).into()
+        }
+        4 => NullBulkString.into(),
+        5 => RespNull.into(),
+        6 => RespNullArray.into(),
+        7 => RespFrame::Boolean(rng.gen()),
+        8 => RespFrame::Double(double(rng)),
+        9 => RespArray::new(children(rng, depth)).into(),
+        10 => RespSet::new(children(rng, depth)).into(),
+        _ => {
+            let mut map = RespMap::new();
+            for child in children(rng, depth) {
+                map.insert(line(rng), child);
+            }
+            map.into()
+        }
+    }
+}
+
+fn children(rng: &mut impl Rng, depth: usize) -> Vec<RespFrame> {
+    let len = rng.gen_range(0..5);
+    (0..len).map(|_| arbitrary_frame(rng, depth - 1)).collect()
+}
+
+// simple strings, errors and map keys cannot contain CR or LF
+fn line(rng: &mut impl Rng) -> String {
+    let len = rng.gen_range(0..16);
+    (0..len)
+        .map(|_| rng.gen_range(' '..='~'))
+        .collect::<String>()
+}
+
+fn integer(rng: &mut impl Rng) -> i64 {
+    match rng.gen_range(0..4) {
+        0 => 0,
+        1 => i64::MIN,
+        2 => i64::MAX,
+        _ => rng.gen(),
+    }
+}
+
+// NaN never compares equal, every other double must survive the trip
+fn double(rng: &mut impl Rng) -> f64 {
+    match rng.gen_range(0..4) {
+        0 => 0.0,
+        1 => rng.gen_range(-1e3..1e3),
+        2 => rng.gen_range(-1e12..1e12),
+        _ => loop {
+            let d = f64::from_bits(rng.gen());
+            if !d.is_nan() {
+                break d;
+            }
+        },
+    }
+}
+
+#[test]
+fn test_round_trip_every_variant() -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for _ in 0..5000 {
+        let frame = arbitrary_frame(&mut rng, MAX_DEPTH);
+        let mut buf = BytesMut::from(&frame.clone().encode()[..]);
+        let decoded = RespFrame::decode(&mut buf)
+            .map_err(|e| anyhow::anyhow!("{:?} failed to decode: {}", frame, e))?;
+        assert_eq!(decoded, frame);
+        assert!(buf.is_empty(), "{:?} left {:?} behind", frame, buf);
+    }
+    Ok(())
+}
+
+// bytes that are mostly RESP syntax, so the decoder gets past the type byte
+fn malformed(rng: &mut impl Rng) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"+-:$*_#,%~0123456789\r\n-x";
+    let len = rng.gen_range(0..64);
+    (0..len)
+        .map(|_| match rng.gen_range(0..4) {
+            0 => rng.gen(),
+            _ => ALPHABET[rng.gen_range(0..ALPHABET.len())],
+        })
+        .collect()
+}
+
+#[test]
+fn test_malformed_input_never_panics() {
+    let mut rng = StdRng::seed_from_u64(0xbad);
+    let oversized: [&[u8]; 6] = [
+        b"$18446744073709551615\r\n",
+        b"$18446744073709551615\r\nabc\r\n",
+        b"$9223372036854775807\r\n",
+        b"*999999999999999\r\n",
+        b"~999999999999999\r\n:1\r\n",
+        b"%999999999999999\r\n+k\r\n",
+    ];
+    let mut inputs = oversized.map(<[u8]>::to_vec).to_vec();
+    for _ in 0..5000 {
+        inputs.push(malformed(&mut rng));
+        // a valid frame cut short or with its tail replaced
+        let mut encoded = arbitrary_frame(&mut rng, MAX_DEPTH).encode();
+        encoded.truncate(rng.gen_range(0..=encoded.len()));
+        encoded.extend(malformed(&mut rng));
+        inputs.push(encoded);
+    }
+    for input in inputs {
+        let mut buf = BytesMut::from(&input[..]);
+        // an error is fine, a frame must consume what it was decoded from
+        if RespFrame::decode(&mut buf).is_ok() {
+            assert!(buf.len() < input.len(), "{:?} consumed nothing", input);
+        }
+    }
+}
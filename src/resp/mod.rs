@@ -3,12 +3,19 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use enum_dispatch::enum_dispatch;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 mod decode;
+mod display;
 mod encode;
+#[cfg(test)]
+mod fuzz;
+
+pub(crate) use decode::frame_length;
 
 /*
 Redis RESP data types:
@@ -31,6 +38,12 @@ Sets: ~<number-of-elements>\r\n<element-1>...<element-n>
 
 #[enum_dispatch(RespEncode)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+// tagged as {"type": "bulk_string", "value": ...}, bulk strings keep their raw bytes
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "value", rename_all = "snake_case")
+)]
 pub enum RespFrame {
     SimpleString(SimpleString),
     SimpleError(SimpleError),
@@ -47,30 +60,39 @@ pub enum RespFrame {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimpleString(pub(crate) String);
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimpleError(pub(crate) String);
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
-pub struct BulkString(pub(crate) Vec<u8>);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BulkString(pub(crate) Bytes);
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RespArray(pub(crate) Vec<RespFrame>);
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NullBulkString;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RespNull;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RespNullArray;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RespMap(BTreeMap<String, RespFrame>);
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RespSet(Vec<RespFrame>);
 
 impl SimpleString {
@@ -87,7 +109,37 @@ impl SimpleError {
 
 impl BulkString {
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
-        BulkString(s.into())
+        BulkString(Bytes::from(s.into()))
+    }
+}
+
+impl From<&str> for BulkString {
+    fn from(s: &str) -> Self {
+        BulkString(Bytes::copy_from_slice(s.as_bytes()))
+    }
+}
+
+impl From<String> for BulkString {
+    fn from(s: String) -> Self {
+        BulkString(Bytes::from(s))
+    }
+}
+
+impl From<Vec<u8>> for BulkString {
+    fn from(v: Vec<u8>) -> Self {
+        BulkString(Bytes::from(v))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(b: Bytes) -> Self {
+        BulkString(b)
+    }
+}
+
+impl From<&str> for RespFrame {
+    fn from(s: &str) -> Self {
+        RespFrame::BulkString(s.into())
     }
 }
 
@@ -101,6 +153,52 @@ impl RespArray {
     pub fn new(v: impl Into<Vec<RespFrame>>) -> Self {
         RespArray(v.into())
     }
+
+    /// Frame a request the way clients send it, one bulk string per part.
+    pub fn command(parts: &[&[u8]]) -> Self {
+        RespArray(
+            parts
+                .iter()
+                .map(|part| BulkString::new(*part).into())
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RespFrame> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for RespArray {
+    type Item = RespFrame;
+    type IntoIter = std::vec::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RespArray {
+    type Item = &'a RespFrame;
+    type IntoIter = std::slice::Iter<'a, RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl From<Vec<RespFrame>> for RespArray {
+    fn from(v: Vec<RespFrame>) -> Self {
+        RespArray(v)
+    }
 }
 
 impl Deref for RespArray {
@@ -127,6 +225,36 @@ impl RespSet {
     pub fn new(v: impl Into<Vec<RespFrame>>) -> Self {
         RespSet(v.into())
     }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RespFrame> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for RespSet {
+    type Item = RespFrame;
+    type IntoIter = std::vec::IntoIter<RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RespSet {
+    type Item = &'a RespFrame;
+    type IntoIter = std::slice::Iter<'a, RespFrame>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 #[enum_dispatch]
@@ -185,3 +313,96 @@ pub enum RespError {
     #[error("Parse float error: {0}")]
     ParseFloatError(#[from] std::num::ParseFloatError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_string_conversions() {
+        let expected = BulkString::new(b"hello");
+        assert_eq!(BulkString::from("hello"), expected);
+        assert_eq!(BulkString::from("hello".to_string()), expected);
+        assert_eq!(BulkString::from(b"hello".to_vec()), expected);
+        assert_eq!(BulkString::from("hello").encode(), b"$5\r\nhello\r\n");
+    }
+
+    #[test]
+    fn test_str_into_frame() {
+        let frame: RespFrame = "world".into();
+        assert_eq!(frame, RespFrame::BulkString(BulkString::new(b"world")));
+        assert_eq!(frame.encode(), b"$5\r\nworld\r\n");
+    }
+
+    #[test]
+    fn test_array_command() {
+        let array = RespArray::command(&[b"set", b"k", b"v"]);
+        assert_eq!(array.encode(), b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n");
+        assert!(RespArray::command(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_array_iter() {
+        let array = RespArray::command(&[b"a", b"b", b"c"]);
+        assert_eq!(array.len(), 3);
+        assert!(!array.is_empty());
+
+        let by_ref: Vec<&RespFrame> = (&array).into_iter().collect();
+        assert_eq!(by_ref, array.iter().collect::<Vec<_>>());
+
+        let owned: Vec<RespFrame> = array.into_iter().collect();
+        assert_eq!(owned, vec!["a".into(), "b".into(), "c".into()]);
+    }
+
+    #[test]
+    fn test_set_iter() {
+        let set = RespSet::new(vec![RespFrame::Integer(1), RespFrame::Integer(2)]);
+        assert_eq!(set.len(), 2);
+        let mut sum = 0;
+        for frame in &set {
+            if let RespFrame::Integer(i) = frame {
+                sum += i;
+            }
+        }
+        assert_eq!(sum, 3);
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            vec![RespFrame::Integer(1), RespFrame::Integer(2)]
+        );
+        assert!(RespSet::new(vec![]).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> anyhow::Result<()> {
+        let mut map = RespMap::new();
+        map.insert("hello".to_string(), RespFrame::Double(1.5));
+        map.insert(
+            "nested".to_string(),
+            RespSet::new(vec![RespFrame::Boolean(true), RespNull.into()]).into(),
+        );
+        let frame: RespFrame = RespArray::new(vec![
+            SimpleString::new("OK".to_string()).into(),
+            SimpleError::new("ERR oops").into(),
+            RespFrame::Integer(-1),
+            BulkString::new(vec![0xff, b'a']).into(),
+            NullBulkString.into(),
+            RespNullArray.into(),
+            map.into(),
+        ])
+        .into();
+
+        let json = serde_json::to_string(&frame)?;
+        assert!(json.contains(r#"{"type":"bulk_string","value":[255,97]}"#));
+        let decoded: RespFrame = serde_json::from_str(&json)?;
+        assert_eq!(decoded, frame);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vec_into_array() {
+        let array: RespArray = vec!["get".into(), "foo".into()].into();
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.encode(), b"*2\r\n$3\r\nget\r\n$3\r\nfoo\r\n");
+    }
+}
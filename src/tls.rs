@@ -0,0 +1,93 @@
+use std::{io::BufReader, path::Path, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use tokio_rustls::{
+    rustls::{crypto::ring, ServerConfig},
+    TlsAcceptor,
+};
+
+/// Build a TLS acceptor from PEM encoded certificate chain and private key files.
+pub fn load_acceptor(
+    cert_path: impl AsRef<Path>,
+    key_path: impl AsRef<Path>,
+) -> Result<TlsAcceptor> {
+    let cert = std::fs::read(cert_path)?;
+    let key = std::fs::read(key_path)?;
+    acceptor_from_pem(&cert, &key)
+}
+
+/// Build a TLS acceptor from in-memory PEM encoded certificate chain and private key.
+pub fn acceptor_from_pem(cert: &[u8], key: &[u8]) -> Result<TlsAcceptor> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert)).collect::<Result<Vec<_>, _>>()?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key))?
+        .ok_or_else(|| anyhow!("no private key found"))?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tokio_rustls::{
+        rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+
+    use super::acceptor_from_pem;
+    use crate::{network, Backend};
+
+    #[tokio::test]
+    async fn test_tls_round_trip() -> Result<()> {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        let acceptor = acceptor_from_pem(
+            cert.cert.pem().as_bytes(),
+            cert.key_pair.serialize_pem().as_bytes(),
+        )?;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(network::serve_tls(
+            listener,
+            Backend::default(),
+            Default::default(),
+            acceptor,
+        ));
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.cert.der().clone())?;
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(config));
+        let stream = TcpStream::connect(addr).await?;
+        let mut stream = connector
+            .connect(ServerName::try_from("localhost")?, stream)
+            .await?;
+
+        stream
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"+OK\r\n");
+
+        stream
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        let mut buf = [0u8; 11];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"$5\r\nworld\r\n");
+        Ok(())
+    }
+}